@@ -0,0 +1,142 @@
+use board::Board;
+use players::Coordinate;
+use robot_player::{get_opponent_sign, robot_rule};
+use rules::{Position, Rules, Status, Value};
+use tablebase;
+
+// Everything there is to know about a position of standard tic-tac-toe, for
+// checking what the robot does in positions from bug reports.
+pub fn analyze(board: Board, to_move: char) -> Result<String, String> {
+    let entry = tablebase::lookup(board, to_move).ok_or("The board can't come up in a game")?;
+    let position = Position::new(board, to_move);
+    let reachable = tablebase::reachable_from(&position);
+    let finished = reachable.iter().filter(|p| p.is_over()).count();
+
+    let mut lines = vec![
+        board.to_string(),
+        String::new(),
+        match position.status() {
+            Status::Won(winner) => format!("Player {} has won", winner),
+            Status::Draw => "The game is a draw".to_string(),
+            Status::Ongoing => format!(
+                "Player {} to move: {}",
+                to_move,
+                describe(to_move, entry.value)
+            ),
+        },
+        format!(
+            "Reachable positions: {}, of which {} are finished games",
+            reachable.len(),
+            finished
+        ),
+    ];
+
+    if position.is_over() {
+        return Ok(lines.join("\n"));
+    }
+
+    lines.push(String::new());
+    lines.push("Moves:".to_string());
+    for chosen in position.legal_moves() {
+        let next = position.play(chosen).expect("legal moves can be played");
+        let value = tablebase::lookup(next.board, next.to_move)
+            .expect("positions after a legal move are in the tablebase")
+            .value
+            .before();
+        let best = if entry.moves.contains(&chosen.coordinate) {
+            "  (best)"
+        } else {
+            ""
+        };
+        lines.push(format!(
+            "    {}  {}{}",
+            square(chosen.coordinate),
+            describe(to_move, value),
+            best
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push(match robot_rule(to_move, board) {
+        Some((rule, chosen)) => format!(
+            "The robot would {} at {}, {}",
+            rule,
+            square(chosen),
+            if entry.moves.contains(&chosen) {
+                "which is a best move"
+            } else {
+                "which is not a best move"
+            }
+        ),
+        None => "The robot has no move".to_string(),
+    });
+    Ok(lines.join("\n"))
+}
+
+// Moves are counted for both players, up to and including the last one.
+fn describe(sign: char, value: Value) -> String {
+    let moves = |count: u32| {
+        if count == 1 {
+            "1 move".to_string()
+        } else {
+            format!("{} moves", count)
+        }
+    };
+    match value {
+        Value::Win(count) => format!("{} wins in {}", sign, moves(count)),
+        Value::Loss(count) => format!("{} wins in {}", get_opponent_sign(sign), moves(count)),
+        Value::Draw => "draw".to_string(),
+    }
+}
+
+fn square((x, y): Coordinate) -> String {
+    format!("{},{}", x, y)
+}
+
+// Who moves next when it isn't given: whoever has fewer marks, and X when
+// both have as many.
+pub fn player_to_move(board: Board) -> char {
+    let count = |sign| board.to_cells().chars().filter(|&c| c == sign).count();
+    if count('O') < count('X') {
+        'O'
+    } else {
+        'X'
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_values_moves_and_the_robot_rule() {
+        let board = Board::from_cells("XX-OO----").unwrap();
+        let report = analyze(board, 'X').unwrap();
+        assert!(report.contains("Player X to move: X wins in 1 move"));
+        assert!(report.contains("Reachable positions: "));
+        assert!(report.contains("    2,0  X wins in 1 move  (best)"));
+        assert!(report.contains("    2,1  draw\n"));
+        assert!(report.contains("    0,2  O wins in 2 moves\n"));
+        assert!(report.ends_with("The robot would win at 2,0, which is a best move"));
+    }
+
+    #[test]
+    fn counts_what_can_follow() {
+        let report = analyze(Board::new(), 'X').unwrap();
+        assert!(report.contains("Player X to move: draw"));
+        assert!(report.contains("Reachable positions: 5478, of which 958 are finished games"));
+
+        let board = Board::from_cells("XXXOO----").unwrap();
+        let report = analyze(board, 'O').unwrap();
+        assert!(report
+            .ends_with("Player X has won\nReachable positions: 1, of which 1 are finished games"));
+    }
+
+    #[test]
+    fn rejects_impossible_boards() {
+        let board = Board::from_cells("XXX------").unwrap();
+        assert!(analyze(board, 'O').is_err());
+        assert_eq!(player_to_move(Board::from_cells("X--------").unwrap()), 'O');
+        assert_eq!(player_to_move(Board::new()), 'X');
+    }
+}
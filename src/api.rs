@@ -0,0 +1,380 @@
+use board::Board;
+use game::is_full_board;
+use http::{Request, Response};
+use json::{self, Value};
+use players::Coordinate;
+use robot_player::get_robot_coordinate;
+use std::collections::HashMap;
+use win_condition::is_winning_board;
+
+const HUMAN_SIGN: char = 'O';
+const ROBOT_SIGN: char = 'X';
+
+// How many games the server keeps before it starts forgetting old ones.
+pub const MAX_GAMES: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    InProgress,
+    HumanWon,
+    RobotWon,
+    Draw,
+}
+
+impl Status {
+    fn as_str(self) -> &'static str {
+        match self {
+            Status::InProgress => "in_progress",
+            Status::HumanWon => "human_won",
+            Status::RobotWon => "robot_won",
+            Status::Draw => "draw",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ApiGame {
+    pub board: Board,
+    pub status: Status,
+    pub robot_move: Option<Coordinate>,
+}
+
+impl ApiGame {
+    fn new(robot_first: bool) -> Result<ApiGame, String> {
+        let game = ApiGame {
+            board: Board::new(),
+            status: Status::InProgress,
+            robot_move: None,
+        };
+        if robot_first {
+            game.play_robot()
+        } else {
+            Ok(game)
+        }
+    }
+
+    fn play_human(self, coordinate: Coordinate) -> Result<ApiGame, String> {
+        let board = self.board.add_value(coordinate, HUMAN_SIGN)?;
+        let game = ApiGame {
+            board,
+            status: get_status(board, Status::HumanWon),
+            robot_move: None,
+        };
+
+        if game.status == Status::InProgress {
+            game.play_robot()
+        } else {
+            Ok(game)
+        }
+    }
+
+    fn play_robot(self) -> Result<ApiGame, String> {
+        let coordinate = get_robot_coordinate(ROBOT_SIGN, self.board)?;
+        let board = self.board.add_value(coordinate, ROBOT_SIGN)?;
+        Ok(ApiGame {
+            board,
+            status: get_status(board, Status::RobotWon),
+            robot_move: Some(coordinate),
+        })
+    }
+
+    fn to_json(self, id: u64) -> String {
+        let robot_move = match self.robot_move {
+            Some((x, y)) => format!("{{\"x\":{},\"y\":{}}}", x, y),
+            None => "null".to_string(),
+        };
+
+        format!(
+            "{{\"id\":{},\"board\":{},\"human\":\"{}\",\"robot\":\"{}\",\"status\":\"{}\",\"robot_move\":{}}}",
+            id,
+            board_to_json(self.board),
+            HUMAN_SIGN,
+            ROBOT_SIGN,
+            self.status.as_str(),
+            robot_move
+        )
+    }
+}
+
+pub fn board_to_json(board: Board) -> String {
+    let rows = board
+        .grid
+        .iter()
+        .map(|row| json::escape(&row.iter().collect::<String>()))
+        .collect::<Vec<String>>();
+    format!("[{}]", rows.join(","))
+}
+
+fn get_status(board: Board, winner: Status) -> Status {
+    if is_winning_board(board) {
+        winner
+    } else if is_full_board(board) {
+        Status::Draw
+    } else {
+        Status::InProgress
+    }
+}
+
+pub struct Api {
+    games: HashMap<u64, ApiGame>,
+    next_id: u64,
+    max_games: usize,
+}
+
+impl Default for Api {
+    fn default() -> Api {
+        Api::new()
+    }
+}
+
+impl Api {
+    pub fn new() -> Api {
+        Api {
+            games: HashMap::new(),
+            next_id: 1,
+            max_games: MAX_GAMES,
+        }
+    }
+
+    pub fn with_max_games(self, max_games: usize) -> Self {
+        Api { max_games, ..self }
+    }
+
+    pub fn handle(&mut self, request: &Request) -> Response {
+        let segments = request
+            .path
+            .split('?')
+            .next()
+            .unwrap_or("")
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<&str>>();
+
+        match (request.method.as_str(), segments.as_slice()) {
+            ("POST", ["games"]) => self.create_game(&request.body),
+            ("GET", ["games", id]) => {
+                self.with_game(id, |id, game| Response::json(200, game.to_json(id)))
+            }
+            ("POST", ["games", id, "moves"]) => {
+                let body = request.body.clone();
+                let id = match id.parse::<u64>() {
+                    Ok(id) => id,
+                    Err(_) => return Response::error(404, "No such game"),
+                };
+                self.make_move(id, &body)
+            }
+            (_, ["games"]) | (_, ["games", _]) | (_, ["games", _, "moves"]) => {
+                Response::error(405, "Method not allowed")
+            }
+            _ => Response::error(404, "Not found"),
+        }
+    }
+
+    fn create_game(&mut self, body: &str) -> Response {
+        let robot_first = if body.trim().is_empty() {
+            false
+        } else {
+            match json::parse_object(body) {
+                Ok(object) => match object.get("robot_first") {
+                    None | Some(&Value::Null) => false,
+                    Some(&Value::Bool(value)) => value,
+                    Some(_) => return Response::error(400, "'robot_first' must be a boolean"),
+                },
+                Err(e) => return Response::error(400, &e),
+            }
+        };
+
+        match ApiGame::new(robot_first) {
+            Ok(game) => {
+                let id = self.next_id;
+                self.next_id += 1;
+                while self.games.len() >= self.max_games.max(1) {
+                    self.evict();
+                }
+                self.games.insert(id, game);
+                Response::json(201, game.to_json(id))
+            }
+            Err(e) => Response::error(500, &e),
+        }
+    }
+
+    // Forgets the oldest finished game, or the oldest game if none has
+    // finished.
+    fn evict(&mut self) {
+        let oldest = |finished: bool| {
+            self.games
+                .iter()
+                .filter(|(_, game)| !finished || game.status != Status::InProgress)
+                .map(|(&id, _)| id)
+                .min()
+        };
+        if let Some(id) = oldest(true).or_else(|| oldest(false)) {
+            self.games.remove(&id);
+        }
+    }
+
+    fn with_game<F>(&self, id: &str, respond: F) -> Response
+    where
+        F: Fn(u64, ApiGame) -> Response,
+    {
+        match id
+            .parse::<u64>()
+            .ok()
+            .and_then(|id| self.games.get(&id).map(|g| (id, *g)))
+        {
+            Some((id, game)) => respond(id, game),
+            None => Response::error(404, "No such game"),
+        }
+    }
+
+    fn make_move(&mut self, id: u64, body: &str) -> Response {
+        let game = match self.games.get(&id) {
+            Some(game) => *game,
+            None => return Response::error(404, "No such game"),
+        };
+        if game.status != Status::InProgress {
+            return Response::error(409, "The game is already over");
+        }
+
+        let coordinate = match parse_move(body) {
+            Ok(coordinate) => coordinate,
+            Err(e) => return Response::error(400, &e),
+        };
+
+        match game.play_human(coordinate) {
+            Ok(game) => {
+                self.games.insert(id, game);
+                Response::json(200, game.to_json(id))
+            }
+            Err(e) => Response::error(400, &e),
+        }
+    }
+}
+
+pub fn parse_move(body: &str) -> Result<Coordinate, String> {
+    let object = json::parse_object(body)?;
+    let value = |key: &str| match object.get(key) {
+        Some(&Value::Number(n)) if n >= 0 => Ok(n as usize),
+        Some(&Value::Number(_)) => Err("The value can't be smaller than 0".to_string()),
+        _ => Err(format!("'{}' must be a number", key)),
+    };
+    Ok((value("x")?, value("y")?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, path: &str, body: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn creating_a_game_returns_empty_board() {
+        let mut api = Api::new();
+        let response = api.handle(&request("POST", "/games", ""));
+        assert_eq!(response.status, 201);
+        assert_eq!(
+            response.body,
+            r#"{"id":1,"board":["---","---","---"],"human":"O","robot":"X","status":"in_progress","robot_move":null}"#
+        );
+    }
+
+    #[test]
+    fn robot_can_start() {
+        let mut api = Api::new();
+        let response = api.handle(&request("POST", "/games", r#"{"robot_first": true}"#));
+        assert!(response.body.contains(r#""board":["---","-X-","---"]"#));
+        assert!(response.body.contains(r#""robot_move":{"x":1,"y":1}"#));
+    }
+
+    #[test]
+    fn robot_answers_human_move() {
+        let mut api = Api::new();
+        api.handle(&request("POST", "/games", ""));
+        let response = api.handle(&request("POST", "/games/1/moves", r#"{"x":0,"y":0}"#));
+        assert_eq!(response.status, 200);
+        assert!(response.body.contains(r#""board":["O--","-X-","---"]"#));
+
+        let response = api.handle(&request("GET", "/games/1", ""));
+        assert!(response.body.contains(r#""board":["O--","-X-","---"]"#));
+    }
+
+    #[test]
+    fn taken_field_is_bad_request() {
+        let mut api = Api::new();
+        api.handle(&request("POST", "/games", r#"{"robot_first": true}"#));
+        let response = api.handle(&request("POST", "/games/1/moves", r#"{"x":1,"y":1}"#));
+        assert_eq!(response.status, 400);
+        assert_eq!(response.body, r#"{"error":"The field is already taken"}"#);
+    }
+
+    #[test]
+    fn malformed_move_is_bad_request() {
+        let mut api = Api::new();
+        api.handle(&request("POST", "/games", ""));
+        let response = api.handle(&request("POST", "/games/1/moves", r#"{"x":1}"#));
+        assert_eq!(response.status, 400);
+    }
+
+    #[test]
+    fn unknown_game_is_not_found() {
+        let mut api = Api::new();
+        assert_eq!(api.handle(&request("GET", "/games/7", "")).status, 404);
+        assert_eq!(
+            api.handle(&request("POST", "/games/x/moves", "{}")).status,
+            404
+        );
+    }
+
+    #[test]
+    fn robot_wins_and_game_is_closed() {
+        let mut api = Api::new();
+        api.handle(&request("POST", "/games", r#"{"robot_first": true}"#));
+        for &(x, y) in &[(1, 0), (0, 1), (2, 1)] {
+            let response = api.handle(&request(
+                "POST",
+                "/games/1/moves",
+                &format!(r#"{{"x":{},"y":{}}}"#, x, y),
+            ));
+            if response.body.contains("robot_won") {
+                let response = api.handle(&request("POST", "/games/1/moves", r#"{"x":2,"y":2}"#));
+                assert_eq!(response.status, 409);
+                return;
+            }
+        }
+        panic!("The robot should have won");
+    }
+
+    #[test]
+    fn old_games_are_forgotten_finished_ones_first() {
+        let mut api = Api::new().with_max_games(2);
+        api.handle(&request("POST", "/games", ""));
+        api.handle(&request("POST", "/games", ""));
+        for &(x, y) in &[(0, 0), (2, 0), (0, 2), (1, 2)] {
+            let body = format!(r#"{{"x":{},"y":{}}}"#, x, y);
+            api.handle(&request("POST", "/games/2/moves", &body));
+        }
+        let response = api.handle(&request("GET", "/games/2", ""));
+        assert!(!response.body.contains("in_progress"));
+
+        api.handle(&request("POST", "/games", ""));
+        assert_eq!(api.handle(&request("GET", "/games/1", "")).status, 200);
+        assert_eq!(api.handle(&request("GET", "/games/2", "")).status, 404);
+
+        api.handle(&request("POST", "/games", ""));
+        assert_eq!(api.handle(&request("GET", "/games/1", "")).status, 404);
+        assert_eq!(api.handle(&request("GET", "/games/4", "")).status, 200);
+    }
+
+    #[test]
+    fn wrong_method_is_not_allowed() {
+        let mut api = Api::new();
+        assert_eq!(api.handle(&request("DELETE", "/games/1", "")).status, 405);
+    }
+}
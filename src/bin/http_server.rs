@@ -0,0 +1,25 @@
+extern crate tic_tac_toe;
+
+use std::env;
+use std::net::TcpListener;
+use std::process;
+use tic_tac_toe::server;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+fn main() {
+    let address = env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+
+    match TcpListener::bind(&address) {
+        Ok(listener) => {
+            println!("Listening on http://{}", address);
+            server::run(listener);
+        }
+        Err(e) => {
+            eprintln!("Could not listen on {}: {}", address, e);
+            process::exit(1);
+        }
+    }
+}
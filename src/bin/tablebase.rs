@@ -0,0 +1,29 @@
+extern crate tic_tac_toe;
+
+use std::env;
+use std::fs;
+use std::process;
+use tic_tac_toe::tablebase;
+
+const DEFAULT_PATH: &str = "src/tablebase.txt";
+
+// Solves standard tic-tac-toe from the rules and writes the table that gets
+// built into the game.
+fn main() {
+    let path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_PATH.to_string());
+
+    let table = tablebase::generate();
+    match fs::write(&path, &table) {
+        Ok(()) => println!(
+            "Wrote {} positions to {}",
+            table.lines().filter(|line| !line.starts_with('#')).count(),
+            path
+        ),
+        Err(e) => {
+            eprintln!("Could not write {}: {}", path, e);
+            process::exit(1);
+        }
+    }
+}
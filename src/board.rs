@@ -1,10 +1,17 @@
+use render::{Render, TextRenderer};
 use std::fmt;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Board {
     pub grid: [[char; 3]; 3],
 }
 
+impl Default for Board {
+    fn default() -> Board {
+        Board::new()
+    }
+}
+
 impl Board {
     pub fn new() -> Board {
         Board {
@@ -31,6 +38,95 @@ impl Board {
         new_board.grid[coordinate.1][coordinate.0] = sign;
         Ok(new_board)
     }
+
+    pub fn to_cells(self) -> String {
+        self.grid.iter().flat_map(|r| r.iter()).collect()
+    }
+
+    pub fn from_cells(cells: &str) -> Result<Board, String> {
+        let values = cells.chars().collect::<Vec<char>>();
+        if values.len() != 9 {
+            return Err("A board needs exactly nine cells".to_string());
+        }
+
+        let mut board = Board::new();
+        for (i, value) in values.into_iter().enumerate() {
+            board.grid[i / 3][i % 3] = value;
+        }
+        Ok(board)
+    }
+}
+
+// A board of any size, for the variants that don't fit on 3x3.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid {
+    pub width: usize,
+    pub height: usize,
+    // Row by row from the top left.
+    cells: Vec<char>,
+}
+
+impl Grid {
+    pub fn new(size: usize) -> Grid {
+        Grid::rectangle(size, size)
+    }
+
+    pub fn rectangle(width: usize, height: usize) -> Grid {
+        Grid {
+            width,
+            height,
+            cells: vec!['-'; width * height],
+        }
+    }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> char {
+        self.cells[y * self.width + x]
+    }
+
+    pub fn add_value(&self, coordinate: (usize, usize), sign: char) -> Result<Grid, String> {
+        if coordinate.0 >= self.width || coordinate.1 >= self.height {
+            return Err("The field is out of bounds".to_string());
+        }
+
+        if self.get(coordinate) != '-' {
+            return Err("The field is already taken".to_string());
+        }
+
+        let mut new_grid = self.clone();
+        new_grid.cells[coordinate.1 * self.width + coordinate.0] = sign;
+        Ok(new_grid)
+    }
+
+    // The lowest empty cell of a column, where a dropped mark comes to rest.
+    pub fn landing(&self, column: usize) -> Option<(usize, usize)> {
+        if column >= self.width {
+            return None;
+        }
+        (0..self.height)
+            .rev()
+            .map(|y| (column, y))
+            .find(|&cell| self.get(cell) == '-')
+    }
+
+    // Like `add_value`, but the mark falls down the column.
+    pub fn drop_value(&self, column: usize, sign: char) -> Result<(Grid, (usize, usize)), String> {
+        if column >= self.width {
+            return Err("The column is out of bounds".to_string());
+        }
+        let cell = self.landing(column).ok_or("The column is full")?;
+        Ok((self.add_value(cell, sign)?, cell))
+    }
+
+    pub fn empty_squares(&self) -> Vec<(usize, usize)> {
+        (0..self.cells.len())
+            .filter(|&i| self.cells[i] == '-')
+            .map(|i| (i % self.width, i / self.width))
+            .collect()
+    }
+
+    pub fn is_full(&self) -> bool {
+        !self.cells.contains(&'-')
+    }
 }
 
 fn transpose_grid(grid: [[char; 3]; 3]) -> [[char; 3]; 3] {
@@ -46,34 +142,6 @@ fn transpose_grid(grid: [[char; 3]; 3]) -> [[char; 3]; 3] {
 
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let values = self
-            .grid
-            .iter()
-            .flat_map(|a| a.iter())
-            .cloned()
-            .collect::<Vec<char>>();
-
-        write!(
-            f,
-            "          0     1     2
-             |     |
-    0     {}  |  {}  |  {}
-        _____|_____|_____
-             |     |
-    1     {}  |  {}  |  {}
-        _____|_____|_____
-             |     |
-    2     {}  |  {}  |  {}
-             |     |     ",
-            values[0],
-            values[1],
-            values[2],
-            values[3],
-            values[4],
-            values[5],
-            values[6],
-            values[7],
-            values[8]
-        )
+        write!(f, "{}", TextRenderer::plain().render(*self, None))
     }
 }
@@ -0,0 +1,166 @@
+use notation::Notation;
+use players::Action;
+
+pub const DEFAULT_SAVE_FILE: &str = "tic_tac_toe.save";
+
+pub const HELP: &str = "Commands:
+    help           Show this list
+    hint           Suggest a move
+    undo           Take back your last move
+    board          Show the board
+    history        List the moves so far
+    offer draw     Ask your opponent to agree to a draw
+    resign         Give up and let your opponent win
+    save [file]    Save the moves so far (default: tic_tac_toe.save)
+    quit           Leave the game
+Anything else is read as a move. In wild games put the mark in front of the
+square, like O 1,1, to place your opponent's mark.";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Help,
+    Hint,
+    Action(Action),
+}
+
+// Commands are recognized by their first word, everything else is handed to
+// the notation as a move.
+pub fn parse_command(input: &str, notation: &dyn Notation) -> Result<Command, String> {
+    let words = input.split_whitespace().collect::<Vec<&str>>();
+    let name = words
+        .first()
+        .map_or(String::new(), |word| word.to_lowercase());
+    let args = words.iter().skip(1).map(|word| word.to_lowercase());
+    let args = args.collect::<Vec<String>>();
+
+    let without_args = |command: Command| {
+        if args.is_empty() {
+            Ok(command)
+        } else {
+            Err(format!("Usage: {}", name))
+        }
+    };
+
+    match name.as_str() {
+        "help" | "?" => without_args(Command::Help),
+        "hint" => without_args(Command::Hint),
+        "undo" => without_args(Command::Action(Action::Undo)),
+        "board" => without_args(Command::Action(Action::ShowBoard)),
+        "history" => without_args(Command::Action(Action::ShowHistory)),
+        "resign" => without_args(Command::Action(Action::Resign)),
+        "quit" | "exit" => without_args(Command::Action(Action::Abandon)),
+        "offer" if args == ["draw"] => Ok(Command::Action(Action::OfferDraw)),
+        "offer" => Err("Usage: offer draw".to_string()),
+        "save" => match words.len() {
+            1 => Ok(Command::Action(Action::Save(DEFAULT_SAVE_FILE.to_string()))),
+            2 => Ok(Command::Action(Action::Save(words[1].to_string()))),
+            _ => Err("Usage: save [file]".to_string()),
+        },
+        "x" | "o" if words.len() > 1 => {
+            let mark = name
+                .to_uppercase()
+                .chars()
+                .next()
+                .expect("the name isn't empty");
+            let square = input.trim_start()[1..].trim();
+            notation
+                .parse(square)
+                .map(|coordinate| Command::Action(Action::PlaceMark(coordinate, mark)))
+                .map_err(|e| format!("{} (type help to see all commands)", e))
+        }
+        _ => notation
+            .parse(input)
+            .map(|coordinate| Command::Action(Action::Place(coordinate)))
+            .map_err(|e| {
+                if name.len() > 2 && name.chars().all(char::is_alphabetic) {
+                    format!("Unknown command '{}', type help to see all commands", name)
+                } else {
+                    format!("{} (type help to see all commands)", e)
+                }
+            }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notation::{Algebraic, AutoDetect};
+
+    #[test]
+    fn commands_take_precedence_over_moves() {
+        assert_eq!(parse_command("help", &AutoDetect), Ok(Command::Help));
+        assert_eq!(parse_command(" HINT ", &AutoDetect), Ok(Command::Hint));
+        assert_eq!(
+            parse_command("offer draw", &AutoDetect),
+            Ok(Command::Action(Action::OfferDraw))
+        );
+        assert_eq!(
+            parse_command("quit", &AutoDetect),
+            Ok(Command::Action(Action::Abandon))
+        );
+    }
+
+    #[test]
+    fn save_uses_default_file() {
+        assert_eq!(
+            parse_command("save", &AutoDetect),
+            Ok(Command::Action(Action::Save(DEFAULT_SAVE_FILE.to_string())))
+        );
+        assert_eq!(
+            parse_command("save Game.txt", &AutoDetect),
+            Ok(Command::Action(Action::Save("Game.txt".to_string())))
+        );
+    }
+
+    #[test]
+    fn wrong_arguments_give_usage() {
+        assert_eq!(
+            parse_command("undo twice", &AutoDetect),
+            Err("Usage: undo".to_string())
+        );
+        assert_eq!(
+            parse_command("offer", &AutoDetect),
+            Err("Usage: offer draw".to_string())
+        );
+        assert_eq!(
+            parse_command("save a b", &AutoDetect),
+            Err("Usage: save [file]".to_string())
+        );
+    }
+
+    #[test]
+    fn other_input_is_a_move() {
+        assert_eq!(
+            parse_command("c1", &Algebraic),
+            Ok(Command::Action(Action::Place((2, 2))))
+        );
+        assert_eq!(
+            parse_command("1 2", &AutoDetect),
+            Ok(Command::Action(Action::Place((1, 2))))
+        );
+    }
+
+    #[test]
+    fn mark_in_front_of_a_move_is_placed() {
+        assert_eq!(
+            parse_command("o 1,2", &AutoDetect),
+            Ok(Command::Action(Action::PlaceMark((1, 2), 'O')))
+        );
+        assert_eq!(
+            parse_command("X b2", &Algebraic),
+            Ok(Command::Action(Action::PlaceMark((1, 1), 'X')))
+        );
+        assert!(parse_command("x 5,5", &AutoDetect).is_err());
+    }
+
+    #[test]
+    fn unknown_words_are_reported_as_commands() {
+        assert_eq!(
+            parse_command("dance", &AutoDetect),
+            Err("Unknown command 'dance', type help to see all commands".to_string())
+        );
+        assert!(parse_command("d4", &Algebraic)
+            .unwrap_err()
+            .ends_with("(type help to see all commands)"));
+    }
+}
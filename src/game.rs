@@ -0,0 +1,773 @@
+use board::Board;
+use notation::{Coordinates, Notation};
+use players::{Action, Coordinate, Move, Player};
+use render::{Render, TextRenderer};
+use rules::{Position, Rules, Status};
+use std::cell::RefCell;
+use std::fs;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+pub trait Observer {
+    fn on_move(&mut self, _sign: char, _coordinate: Coordinate, _board: Board) {}
+
+    fn on_game_over(&mut self, _board: Board, _message: &str) {}
+
+    fn on_outcome(&mut self, _outcome: Outcome) {}
+}
+
+// An in-memory output that can be shared between the game and its players,
+// so everything they print ends up in one transcript.
+#[derive(Clone, Default)]
+pub struct Transcript {
+    buffer: Rc<RefCell<Vec<u8>>>,
+}
+
+impl Transcript {
+    pub fn new() -> Transcript {
+        Transcript::default()
+    }
+
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.buffer.borrow()).into_owned()
+    }
+}
+
+impl Write for Transcript {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuleSet {
+    Standard,
+    // Whoever completes three in a row loses.
+    Misere,
+    // Either player may place either mark, and whoever completes three in a
+    // row wins.
+    Wild,
+}
+
+pub const RULE_SETS: [RuleSet; 3] = [RuleSet::Standard, RuleSet::Misere, RuleSet::Wild];
+
+impl RuleSet {
+    pub fn name(self) -> &'static str {
+        match self {
+            RuleSet::Standard => "standard",
+            RuleSet::Misere => "misere",
+            RuleSet::Wild => "wild",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RuleSet> {
+        RULE_SETS.iter().cloned().find(|rules| rules.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Won(char),
+    Draw,
+    Abandoned(char),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Ending {
+    Played,
+    Abandoned,
+    Resigned,
+    DrawAgreed,
+}
+
+pub struct Game {
+    // The board, the rules and the moves so far.
+    position: Position,
+    players: [Box<dyn Player>; 2],
+    observers: Vec<Box<dyn Observer>>,
+    output: Box<dyn Write>,
+    renderer: Box<dyn Render>,
+    notation: Box<dyn Notation>,
+    current_player: usize,
+    is_over: bool,
+    ending: Ending,
+}
+
+impl Game {
+    pub fn new(players: [Box<dyn Player>; 2]) -> Game {
+        Game {
+            position: Position::new(Board::new(), players[0].sign()),
+            players,
+            observers: Vec::new(),
+            output: Box::new(io::stdout()),
+            renderer: Box::new(TextRenderer::plain()),
+            notation: Box::new(Coordinates),
+            current_player: 0,
+            is_over: false,
+            ending: Ending::Played,
+        }
+    }
+
+    pub fn with_observer(mut self, observer: Box<dyn Observer>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    pub fn with_output(self, output: Box<dyn Write>) -> Self {
+        Game { output, ..self }
+    }
+
+    pub fn with_renderer(self, renderer: Box<dyn Render>) -> Self {
+        Game { renderer, ..self }
+    }
+
+    // The notation moves are echoed in after each turn.
+    pub fn with_notation(self, notation: Box<dyn Notation>) -> Self {
+        Game { notation, ..self }
+    }
+
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.position.rules = rules;
+        self
+    }
+
+    fn print(&mut self, text: &str) {
+        let _ = writeln!(self.output, "{}", text);
+    }
+
+    fn next_turn(&mut self) {
+        self.is_over = self.position.is_over();
+        self.current_player = 1 - self.current_player;
+    }
+
+    // Runs turns until the game ends. Rejected moves and commands that don't
+    // end the turn just go round again, so a player can get any number of
+    // them wrong.
+    pub fn play(mut self) -> Self {
+        while !self.is_over {
+            let board = self.position.board;
+            let sign = self.players[self.current_player].sign();
+            match self.players[self.current_player].choose_action(board) {
+                Ok(Action::Place(coordinate)) => self.place_choice(coordinate, sign),
+                Ok(Action::PlaceMark(coordinate, mark)) => self.place_choice(coordinate, mark),
+                Ok(Action::Abandon) => self.end(Ending::Abandoned),
+                Ok(Action::Resign) => self.end(Ending::Resigned),
+                Ok(Action::OfferDraw) => self.offer_draw(),
+                Ok(Action::Undo) => self.undo(),
+                Ok(Action::ShowBoard) => {
+                    let board = self.renderer.render(self.position.board, None);
+                    self.print(&board);
+                }
+                Ok(Action::ShowHistory) => self.show_history(),
+                Ok(Action::Save(path)) => self.save(&path),
+                Err(e) => self.print_error(&e),
+            }
+        }
+
+        let message = self.get_final_message();
+        self.print("Game over");
+        self.print(&message);
+        let board = self.position.board;
+        for player in self.players.iter_mut() {
+            player.game_over(board, &message);
+        }
+        let outcome = self.outcome();
+        for observer in self.observers.iter_mut() {
+            observer.on_game_over(board, &message);
+            observer.on_outcome(outcome);
+        }
+        self
+    }
+
+    fn end(&mut self, ending: Ending) {
+        self.is_over = true;
+        self.ending = ending;
+    }
+
+    fn offer_draw(&mut self) {
+        let board = self.position.board;
+        let opponent = 1 - self.current_player;
+        if self.players[opponent].accept_draw(board) {
+            self.end(Ending::DrawAgreed)
+        } else {
+            let sign = self.players[opponent].sign();
+            self.print(&format!("Player {} declined the draw", sign));
+        }
+    }
+
+    // Takes back the current player's last move and everything played after
+    // it, so it's their turn again.
+    fn undo(&mut self) {
+        let sign = self.players[self.current_player].sign();
+        if !self.position.history().iter().any(|m| m.player == sign) {
+            return self.print_error("There is nothing to undo");
+        }
+
+        while let Some(undone) = self.position.last_move() {
+            self.position = self.position.undo().expect("there is a move to undo");
+            if undone.player == sign {
+                break;
+            }
+        }
+
+        self.print(&format!("\nPlayer {} took back their last move:\n", sign));
+        let board = self.renderer.render(self.position.board, None);
+        self.print(&board);
+    }
+
+    fn show_history(&mut self) {
+        let moves = self
+            .position
+            .history()
+            .iter()
+            .enumerate()
+            .map(|(i, &m)| format!("{}. {} {}", i + 1, m.player, self.format_move(m)))
+            .collect::<Vec<String>>();
+
+        if moves.is_empty() {
+            self.print("No moves have been played yet");
+        } else {
+            self.print(&moves.join("\n"));
+        }
+    }
+
+    // Writes one move per line, e.g. `X 1,2`, or `X O 1,2` in wild games
+    // where the mark is given after the player.
+    fn save(&mut self, path: &str) {
+        let rules = self.position.rules;
+        let contents = self
+            .position
+            .history()
+            .iter()
+            .map(|m| {
+                let (x, y) = m.coordinate;
+                match rules {
+                    RuleSet::Wild => format!("{} {} {},{}\n", m.player, m.mark, x, y),
+                    _ => format!("{} {},{}\n", m.player, x, y),
+                }
+            })
+            .collect::<String>();
+
+        match fs::write(path, contents) {
+            Ok(()) => self.print(&format!("Saved the game to {}", path)),
+            Err(e) => self.print(&format!("Could not save the game to {}: {}", path, e)),
+        }
+    }
+
+    fn print_error(&mut self, error: &str) {
+        let board = self.renderer.render(self.position.board, None);
+        self.print(error);
+        self.print(&board);
+    }
+
+    // The mark is shown along with the square in wild games, where it isn't
+    // implied by the player.
+    fn format_move(&self, m: Move) -> String {
+        let square = self.notation.format(m.coordinate);
+        match self.position.rules {
+            RuleSet::Wild => format!("{} {}", m.mark, square),
+            _ => square,
+        }
+    }
+
+    fn place_choice(&mut self, coordinate: Coordinate, mark: char) {
+        let sign = self.players[self.current_player].sign();
+        let played = Move {
+            player: sign,
+            mark,
+            coordinate,
+        };
+
+        self.position = match self.position.play(played) {
+            Ok(position) => position,
+            Err(e) => return self.print_error(&e),
+        };
+        let board = self.position.board;
+        for observer in self.observers.iter_mut() {
+            observer.on_move(sign, coordinate, board);
+        }
+        self.next_turn();
+        let played = self.format_move(played);
+        self.print(&format!(
+            "\nBoard after player {}'s turn ({}):\n",
+            sign, played
+        ));
+        let last_move = self.position.last_move().map(|m| m.coordinate);
+        let board = self.renderer.render(self.position.board, last_move);
+        self.print(&board);
+    }
+
+    pub fn get_final_message(&self) -> String {
+        let sign = self.players[self.current_player].sign();
+        let opponent = self.players[1 - self.current_player].sign();
+
+        match self.ending {
+            Ending::Abandoned => format!("Player {} left the game", sign),
+            Ending::Resigned => format!("Player {} resigned, player {} won!", sign, opponent),
+            Ending::DrawAgreed => "It's a draw by agreement".to_string(),
+            Ending::Played => match (self.position.status(), self.position.rules) {
+                (Status::Won(winner), RuleSet::Misere) => format!(
+                    "Player {} completed three in a row, player {} won!",
+                    opponent, winner
+                ),
+                (Status::Won(winner), _) => format!("Player {} won!", winner),
+                _ => "It's a tie!".to_string(),
+            },
+        }
+    }
+
+    // Only meaningful once `play` has returned.
+    pub fn outcome(&self) -> Outcome {
+        let sign = self.players[self.current_player].sign();
+        let opponent = self.players[1 - self.current_player].sign();
+
+        match self.ending {
+            Ending::Abandoned => Outcome::Abandoned(sign),
+            Ending::Resigned => Outcome::Won(opponent),
+            Ending::DrawAgreed => Outcome::Draw,
+            Ending::Played => match self.position.status() {
+                Status::Won(winner) => Outcome::Won(winner),
+                _ => Outcome::Draw,
+            },
+        }
+    }
+
+    pub fn into_players(self) -> [Box<dyn Player>; 2] {
+        self.players
+    }
+}
+
+pub fn is_full_board(board: Board) -> bool {
+    !board
+        .grid
+        .iter()
+        .flat_map(|r| r.iter())
+        .collect::<Vec<&char>>()
+        .contains(&&'-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use board::Board;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct DummyPlayer {
+        sign: char,
+    }
+
+    impl Player for DummyPlayer {
+        fn sign(&self) -> char {
+            self.sign
+        }
+
+        fn choose_action(&mut self, _board: Board) -> Result<Action, String> {
+            Err("Not implemented".to_string())
+        }
+    }
+
+    struct ScriptedPlayer {
+        sign: char,
+        actions: Vec<Action>,
+    }
+
+    impl Player for ScriptedPlayer {
+        fn sign(&self) -> char {
+            self.sign
+        }
+
+        fn choose_action(&mut self, _board: Board) -> Result<Action, String> {
+            Ok(self.actions.remove(0))
+        }
+    }
+
+    fn dummy_players() -> [Box<dyn Player>; 2] {
+        [
+            Box::new(DummyPlayer { sign: 'O' }),
+            Box::new(DummyPlayer { sign: 'X' }),
+        ]
+    }
+
+    #[test]
+    fn full_board_is_full() {
+        let board = Board {
+            grid: [['X'; 3], ['X'; 3], ['X'; 3]],
+        };
+        assert!(is_full_board(board));
+    }
+
+    #[test]
+    fn empty_board_is_not_full() {
+        let board = Board {
+            grid: [['-'; 3], ['-'; 3], ['-'; 3]],
+        };
+        assert!(!is_full_board(board));
+    }
+
+    #[test]
+    fn slightly_populated_board_is_not_full() {
+        let board = Board {
+            grid: [['-', 'X', 'O'], ['-', '-', '-'], ['O', '-', 'X']],
+        };
+        assert!(!is_full_board(board));
+    }
+
+    #[test]
+    fn add_value_in_empty_field_adds_value() {
+        let sign = 'X';
+        let board = Board::new();
+        let player = DummyPlayer { sign };
+        let result_board = board.add_value((0, 0), player.sign()).unwrap();
+        assert_eq!(result_board.grid[0][0], sign);
+    }
+
+    #[test]
+    fn add_value_with_custom_sign_uses_sign() {
+        let sign = 'A';
+        let board = Board::new();
+        let player = DummyPlayer { sign };
+        let result_board = board.add_value((0, 0), player.sign()).unwrap();
+        assert_eq!(result_board.grid[0][0], sign);
+    }
+
+    #[test]
+    fn add_value_outside_of_bounds_is_invalid() {
+        let board = Board::new();
+
+        let player = DummyPlayer { sign: 'X' };
+
+        let result = board.add_value((3, 3), player.sign());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn add_value_to_existing_field_is_invalid() {
+        let board = Board {
+            grid: [['X'; 3], ['-'; 3], ['-'; 3]],
+        };
+
+        let player = DummyPlayer { sign: 'X' };
+
+        let result = board.add_value((0, 0), player.sign());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn play_game_with_game_over_does_not_panic() {
+        let mut game = Game::new(dummy_players());
+        game.is_over = true;
+
+        game.play();
+    }
+
+    #[test]
+    fn next_turn_switches_current_player() {
+        let mut game = Game::new(dummy_players());
+        let initial_player = game.players[game.current_player].sign();
+
+        game.next_turn();
+        let players_are_same = initial_player == game.players[game.current_player].sign();
+        assert!(!players_are_same)
+    }
+
+    struct RecordingObserver {
+        events: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Observer for RecordingObserver {
+        fn on_move(&mut self, sign: char, coordinate: Coordinate, _board: Board) {
+            self.events
+                .borrow_mut()
+                .push(format!("{} {},{}", sign, coordinate.0, coordinate.1));
+        }
+
+        fn on_game_over(&mut self, _board: Board, message: &str) {
+            self.events.borrow_mut().push(message.to_string());
+        }
+    }
+
+    #[test]
+    fn observers_see_moves_and_result() {
+        let players: [Box<dyn Player>; 2] = [
+            Box::new(ScriptedPlayer {
+                sign: 'O',
+                actions: vec![Action::Place((0, 0))],
+            }),
+            Box::new(ScriptedPlayer {
+                sign: 'X',
+                actions: vec![Action::Abandon],
+            }),
+        ];
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let observer = RecordingObserver {
+            events: Rc::clone(&events),
+        };
+
+        Game::new(players).with_observer(Box::new(observer)).play();
+
+        assert_eq!(*events.borrow(), vec!["O 0,0", "Player X left the game"]);
+    }
+
+    #[test]
+    fn abandoning_ends_the_game() {
+        let players: [Box<dyn Player>; 2] = [
+            Box::new(ScriptedPlayer {
+                sign: 'O',
+                actions: vec![Action::Place((0, 0))],
+            }),
+            Box::new(ScriptedPlayer {
+                sign: 'X',
+                actions: vec![Action::Abandon],
+            }),
+        ];
+
+        let game = Game::new(players).play();
+        assert_eq!(game.get_final_message(), "Player X left the game");
+    }
+
+    fn scripted_players(o: Vec<Action>, x: Vec<Action>) -> [Box<dyn Player>; 2] {
+        [
+            Box::new(ScriptedPlayer {
+                sign: 'O',
+                actions: o,
+            }),
+            Box::new(ScriptedPlayer {
+                sign: 'X',
+                actions: x,
+            }),
+        ]
+    }
+
+    struct StubbornPlayer {
+        mistakes: usize,
+    }
+
+    impl Player for StubbornPlayer {
+        fn sign(&self) -> char {
+            'O'
+        }
+
+        fn choose_action(&mut self, _board: Board) -> Result<Action, String> {
+            if self.mistakes == 0 {
+                return Ok(Action::Abandon);
+            }
+            self.mistakes -= 1;
+            if self.mistakes.is_multiple_of(2) {
+                Err("Not a move".to_string())
+            } else {
+                Ok(Action::Place((3, 3)))
+            }
+        }
+    }
+
+    #[test]
+    fn any_number_of_mistakes_fits_on_the_stack() {
+        let players: [Box<dyn Player>; 2] = [
+            Box::new(StubbornPlayer { mistakes: 100_000 }),
+            Box::new(DummyPlayer { sign: 'X' }),
+        ];
+
+        let game = Game::new(players).with_output(Box::new(io::sink())).play();
+        assert_eq!(game.get_final_message(), "Player O left the game");
+    }
+
+    #[test]
+    fn undo_takes_back_the_last_round() {
+        let players = scripted_players(
+            vec![Action::Place((0, 0)), Action::Undo, Action::Place((2, 2))],
+            vec![Action::Place((1, 1)), Action::Abandon],
+        );
+
+        let game = Game::new(players).with_output(Box::new(io::sink())).play();
+        assert_eq!(game.position.board.grid[0][0], '-');
+        assert_eq!(game.position.board.grid[1][1], '-');
+        assert_eq!(game.position.board.grid[2][2], 'O');
+        assert_eq!(
+            game.position.history(),
+            [Move {
+                player: 'O',
+                mark: 'O',
+                coordinate: (2, 2)
+            }]
+        );
+    }
+
+    #[test]
+    fn undo_without_moves_is_an_error() {
+        let players = scripted_players(vec![Action::Undo, Action::Abandon], vec![]);
+
+        let game = Game::new(players).with_output(Box::new(io::sink())).play();
+        assert_eq!(game.get_final_message(), "Player O left the game");
+    }
+
+    #[test]
+    fn resigning_lets_the_opponent_win() {
+        let players = scripted_players(vec![Action::Place((0, 0))], vec![Action::Resign]);
+
+        let game = Game::new(players).with_output(Box::new(io::sink())).play();
+        assert_eq!(game.get_final_message(), "Player X resigned, player O won!");
+    }
+
+    #[test]
+    fn declined_draw_continues_the_game() {
+        let players = scripted_players(vec![Action::OfferDraw, Action::Resign], vec![]);
+
+        let game = Game::new(players).with_output(Box::new(io::sink())).play();
+        assert_eq!(game.get_final_message(), "Player O resigned, player X won!");
+    }
+
+    #[test]
+    fn completing_a_line_loses_in_misere() {
+        let players = scripted_players(
+            vec![
+                Action::Place((0, 0)),
+                Action::Place((1, 0)),
+                Action::Place((2, 0)),
+            ],
+            vec![Action::Place((0, 1)), Action::Place((0, 2))],
+        );
+
+        let game = Game::new(players)
+            .with_rules(RuleSet::Misere)
+            .with_output(Box::new(io::sink()))
+            .play();
+        assert_eq!(
+            game.get_final_message(),
+            "Player O completed three in a row, player X won!"
+        );
+        assert_eq!(game.outcome(), Outcome::Won('X'));
+    }
+
+    #[test]
+    fn own_mark_only_outside_wild_games() {
+        let players = scripted_players(
+            vec![Action::PlaceMark((0, 0), 'X'), Action::Abandon],
+            vec![],
+        );
+
+        let game = Game::new(players).with_output(Box::new(io::sink())).play();
+        assert_eq!(game.position.board, Board::new());
+    }
+
+    #[test]
+    fn completing_a_line_in_any_mark_wins_in_wild() {
+        let players = scripted_players(
+            vec![Action::Place((0, 0)), Action::Place((2, 2))],
+            vec![
+                Action::PlaceMark((1, 1), 'Z'),
+                Action::PlaceMark((1, 0), 'O'),
+                Action::PlaceMark((2, 0), 'O'),
+            ],
+        );
+
+        let game = Game::new(players)
+            .with_rules(RuleSet::Wild)
+            .with_output(Box::new(io::sink()))
+            .play();
+        assert_eq!(game.get_final_message(), "Player X won!");
+        assert_eq!(game.outcome(), Outcome::Won('X'));
+        assert_eq!(game.position.history().len(), 4);
+        assert_eq!(game.position.history()[3].player, 'X');
+        assert_eq!(game.position.history()[3].mark, 'O');
+    }
+}
+
+#[cfg(test)]
+mod golden_tests {
+    use super::*;
+    use notation::Algebraic;
+    use robot_player::RobotPlayer;
+    use std::env;
+    use std::io::Cursor;
+    use user_input::HumanPlayer;
+
+    // Set `UPDATE_GOLDEN=1` to rewrite the expected transcripts after an
+    // intended change to the output.
+    fn assert_transcript(name: &str, transcript: &Transcript) {
+        let path = format!("{}/tests/golden/{}.txt", env!("CARGO_MANIFEST_DIR"), name);
+        let actual = transcript.contents();
+
+        if env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&path, &actual).unwrap();
+        }
+        let expected = fs::read_to_string(&path).unwrap();
+        assert_eq!(actual, expected, "the transcript differs from {}", path);
+    }
+
+    fn human(sign: char, input: &'static str, transcript: &Transcript) -> HumanPlayer {
+        HumanPlayer::new(sign)
+            .with_input(Box::new(Cursor::new(input)))
+            .with_output(Box::new(transcript.clone()))
+    }
+
+    #[test]
+    fn robot_beats_careless_human() {
+        let transcript = Transcript::new();
+        let players: [Box<dyn Player>; 2] = [
+            Box::new(human('O', "0,1\n2,1\n0,2\n", &transcript)),
+            Box::new(RobotPlayer::new('X')),
+        ];
+
+        let game = Game::new(players)
+            .with_output(Box::new(transcript.clone()))
+            .play();
+
+        assert_eq!(game.get_final_message(), "Player X won!");
+        assert_transcript("robot_beats_careless_human", &transcript);
+    }
+
+    #[test]
+    fn shell_commands_and_mistakes() {
+        let transcript = Transcript::new();
+        let input = "help\n\n3,3\n1,1\n1,1\nundo\nhistory\n0,0\noffer draw\nquit\n";
+        let players: [Box<dyn Player>; 2] = [
+            Box::new(human('O', input, &transcript)),
+            Box::new(RobotPlayer::new('X')),
+        ];
+
+        let game = Game::new(players)
+            .with_output(Box::new(transcript.clone()))
+            .play();
+
+        assert_eq!(game.get_final_message(), "Player O left the game");
+        assert_transcript("shell_commands_and_mistakes", &transcript);
+    }
+
+    #[test]
+    fn two_humans_in_algebraic_notation() {
+        let transcript = Transcript::new();
+        let players: [Box<dyn Player>; 2] = [
+            Box::new(human('O', "a1\nb2\nc3\n", &transcript).with_notation(Box::new(Algebraic))),
+            Box::new(human('X', "a2\na3\n", &transcript).with_notation(Box::new(Algebraic))),
+        ];
+
+        let game = Game::new(players)
+            .with_output(Box::new(transcript.clone()))
+            .with_notation(Box::new(Algebraic))
+            .play();
+
+        assert_eq!(game.get_final_message(), "Player O won!");
+        assert_transcript("two_humans_in_algebraic_notation", &transcript);
+    }
+
+    #[test]
+    fn input_ending_mid_game() {
+        let transcript = Transcript::new();
+        let players: [Box<dyn Player>; 2] = [
+            Box::new(RobotPlayer::new('X')),
+            Box::new(human('O', "0,1\n", &transcript)),
+        ];
+
+        let game = Game::new(players)
+            .with_output(Box::new(transcript.clone()))
+            .play();
+
+        assert_eq!(game.get_final_message(), "Player O left the game");
+        assert_transcript("input_ending_mid_game", &transcript);
+    }
+}
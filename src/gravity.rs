@@ -0,0 +1,401 @@
+use board::Grid;
+use players::Coordinate;
+use random::Random;
+use robot_player::{get_opponent_sign, Difficulty};
+use rules::{Rules, Status};
+use user_input::parse_column;
+use variant::{Variant, VariantPlayer};
+use win_condition::grid_lines;
+
+// Marks drop down a column to the lowest empty cell, like in Connect Four,
+// so a move is only the column. Four in a row wins and a full board is a
+// draw. Columns are numbered from 1 at the left.
+
+pub const COLUMNS: usize = 7;
+pub const ROWS: usize = 6;
+pub const RUN: usize = 4;
+
+// The directions a run can go in from a cell, each also walked backwards.
+const DIRECTIONS: [(isize, isize); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gravity {
+    pub grid: Grid,
+    pub to_move: char,
+    // Only the mark just dropped can have completed a run.
+    last: Option<Coordinate>,
+}
+
+impl Default for Gravity {
+    fn default() -> Gravity {
+        Gravity::new(COLUMNS, ROWS)
+    }
+}
+
+impl Gravity {
+    pub fn new(columns: usize, rows: usize) -> Gravity {
+        Gravity {
+            grid: Grid::rectangle(columns, rows),
+            to_move: 'X',
+            last: None,
+        }
+    }
+
+    pub fn play(&self, column: usize) -> Result<Gravity, String> {
+        if self.is_over() {
+            return Err("The game is already over".to_string());
+        }
+
+        let (grid, cell) = self.grid.drop_value(column, self.to_move)?;
+        Ok(Gravity {
+            grid,
+            to_move: get_opponent_sign(self.to_move),
+            last: Some(cell),
+        })
+    }
+
+    pub fn legal_moves(&self) -> Vec<usize> {
+        if self.is_over() {
+            return Vec::new();
+        }
+        (0..self.grid.width)
+            .filter(|&column| self.grid.landing(column).is_some())
+            .collect()
+    }
+
+    pub fn winner(&self) -> Option<char> {
+        self.last
+            .filter(|&cell| completes_run(&self.grid, cell))
+            .map(|cell| self.grid.get(cell))
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some() || self.grid.is_full()
+    }
+}
+
+impl Rules for Gravity {
+    type Move = usize;
+
+    fn to_move(&self) -> char {
+        self.to_move
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        Gravity::legal_moves(self)
+    }
+
+    fn play(&self, column: usize) -> Result<Gravity, String> {
+        Gravity::play(self, column)
+    }
+
+    fn status(&self) -> Status {
+        match Gravity::winner(self) {
+            Some(sign) => Status::Won(sign),
+            None if Gravity::is_over(self) => Status::Draw,
+            None => Status::Ongoing,
+        }
+    }
+}
+
+impl Variant for Gravity {
+    fn prompt(&self) -> String {
+        format!("enter a column from 1 to {}", self.grid.width)
+    }
+
+    fn parse_move(&self, input: &str) -> Result<usize, String> {
+        parse_column(input, self.grid.width)
+    }
+
+    fn format_move(&self, column: usize) -> String {
+        (column + 1).to_string()
+    }
+
+    fn render(&self) -> String {
+        render(self)
+    }
+}
+
+// Whether the mark in `cell` is part of a run of four.
+fn completes_run(grid: &Grid, (x, y): Coordinate) -> bool {
+    let mark = grid.get((x, y));
+    let count = |(dx, dy): (isize, isize)| {
+        (1..RUN as isize)
+            .map(|step| (x as isize + dx * step, y as isize + dy * step))
+            .take_while(|&(x, y)| {
+                x >= 0
+                    && y >= 0
+                    && (x as usize) < grid.width
+                    && (y as usize) < grid.height
+                    && grid.get((x as usize, y as usize)) == mark
+            })
+            .count()
+    };
+    DIRECTIONS
+        .iter()
+        .any(|&(dx, dy)| 1 + count((dx, dy)) + count((-dx, -dy)) >= RUN)
+}
+
+pub fn render(game: &Gravity) -> String {
+    let header = (1..=game.grid.width)
+        .map(|column| column.to_string())
+        .collect::<Vec<String>>();
+    let mut lines = vec![header.join(" ")];
+    for y in 0..game.grid.height {
+        let row = (0..game.grid.width)
+            .map(|x| game.grid.get((x, y)).to_string())
+            .collect::<Vec<String>>();
+        lines.push(row.join(" "));
+    }
+    lines.join("\n")
+}
+
+// Looks two moves ahead on easy, four on medium and seven on hard.
+pub struct GravityRobot {
+    sign: char,
+    difficulty: Difficulty,
+    random: Random,
+}
+
+impl GravityRobot {
+    pub fn new(sign: char) -> GravityRobot {
+        GravityRobot {
+            sign,
+            difficulty: Difficulty::Hard,
+            random: Random::from_time(),
+        }
+    }
+
+    pub fn with_difficulty(self, difficulty: Difficulty) -> Self {
+        GravityRobot { difficulty, ..self }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        GravityRobot {
+            random: Random::new(seed),
+            ..self
+        }
+    }
+}
+
+impl VariantPlayer<Gravity> for GravityRobot {
+    fn sign(&self) -> char {
+        self.sign
+    }
+
+    fn choose_move(&mut self, game: &Gravity) -> Result<Option<usize>, String> {
+        let depth = match self.difficulty {
+            Difficulty::Easy => 2,
+            Difficulty::Medium => 4,
+            Difficulty::Hard => 7,
+        };
+        // Ties go to a random one of the best columns so games vary.
+        let best = search(game, depth);
+        self.random
+            .choose(&best)
+            .map(|&column| Some(column))
+            .ok_or_else(|| "No choice found".to_string())
+    }
+}
+
+const WIN: i32 = 1_000_000;
+// What a run with 0 to 3 of one player's marks and nothing else is worth to
+// them.
+const RUN_WEIGHTS: [i32; RUN] = [0, 1, 8, 64];
+
+// The equally good best columns for the player to move, looking `depth`
+// moves ahead.
+pub fn search(game: &Gravity, depth: u32) -> Vec<usize> {
+    let lines = grid_lines(game.grid.width, game.grid.height, RUN);
+    let mut best = Vec::new();
+    let mut best_score = -WIN * 2;
+
+    for column in ordered_columns(game) {
+        let next = game.play(column).expect("legal moves can be played");
+        // Only moves at least as good as the best so far matter, so the
+        // window stays open by one to keep ties.
+        let score = -value(&next, &lines, depth.max(1) - 1, -WIN * 2, 1 - best_score);
+        if score > best_score {
+            best = vec![column];
+            best_score = score;
+        } else if score == best_score {
+            best.push(column);
+        }
+    }
+    best
+}
+
+// How good the position is for the player to move, sooner wins counting for
+// more.
+fn value(game: &Gravity, lines: &[Vec<Coordinate>], depth: u32, mut alpha: i32, beta: i32) -> i32 {
+    if game.winner().is_some() {
+        return -WIN - depth as i32;
+    }
+    if game.grid.is_full() {
+        return 0;
+    }
+    if depth == 0 {
+        return evaluate(game, lines);
+    }
+
+    let mut best = -WIN * 2;
+    for column in ordered_columns(game) {
+        let next = game.play(column).expect("legal moves can be played");
+        let score = -value(&next, lines, depth - 1, -beta, -alpha);
+        best = best.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
+
+// Alpha-beta cuts the most when the best move comes first. A column that
+// wins straight away goes first, then one that stops the opponent from
+// winning there, then the rest from the center out since the middle columns
+// are part of the most runs.
+fn ordered_columns(game: &Gravity) -> Vec<usize> {
+    let wins_with = |column: usize, sign: char| {
+        game.grid
+            .drop_value(column, sign)
+            .is_ok_and(|(grid, cell)| completes_run(&grid, cell))
+    };
+    let opponent = get_opponent_sign(game.to_move);
+    let center = game.grid.width - 1;
+
+    let mut columns = game.legal_moves();
+    columns.sort_by_key(|&column| {
+        (
+            !wins_with(column, game.to_move),
+            !wins_with(column, opponent),
+            (2 * column).abs_diff(center),
+        )
+    });
+    columns
+}
+
+fn evaluate(game: &Gravity, lines: &[Vec<Coordinate>]) -> i32 {
+    let opponent = get_opponent_sign(game.to_move);
+    lines
+        .iter()
+        .map(|line| {
+            let count = |mark: char| line.iter().filter(|&&c| game.grid.get(c) == mark).count();
+            match (count(game.to_move), count(opponent)) {
+                (marks, 0) => RUN_WEIGHTS[marks.min(RUN - 1)],
+                (0, marks) => -RUN_WEIGHTS[marks.min(RUN - 1)],
+                _ => 0,
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::Outcome;
+    use std::io;
+    use variant::play;
+
+    fn after(columns: &[usize]) -> Gravity {
+        columns.iter().fold(Gravity::default(), |game, &column| {
+            game.play(column).unwrap()
+        })
+    }
+
+    #[test]
+    fn marks_fall_to_the_bottom() {
+        let game = after(&[3, 3, 2]);
+        assert_eq!(game.grid.get((3, 5)), 'X');
+        assert_eq!(game.grid.get((3, 4)), 'O');
+        assert_eq!(game.grid.get((2, 5)), 'X');
+        assert_eq!(game.grid.landing(3), Some((3, 3)));
+        assert_eq!(game.to_move, 'O');
+    }
+
+    #[test]
+    fn full_columns_are_rejected() {
+        let game = after(&[0, 0, 0, 0, 0, 0]);
+        assert_eq!(game.play(0), Err("The column is full".to_string()));
+        assert!(game.play(7).is_err());
+        assert!(!game.legal_moves().contains(&0));
+    }
+
+    #[test]
+    fn four_in_a_row_wins() {
+        let game = after(&[0, 0, 1, 1, 2, 2]);
+        assert_eq!(game.winner(), None);
+        let game = game.play(3).unwrap();
+        assert_eq!(game.winner(), Some('X'));
+        assert!(game.legal_moves().is_empty());
+
+        // A rising diagonal from the bottom left.
+        let game = after(&[0, 1, 1, 2, 2, 3, 2, 3, 3, 6, 3]);
+        assert_eq!(game.winner(), Some('X'));
+    }
+
+    #[test]
+    fn renders_columns_from_one() {
+        let expected = "1 2 3 4 5 6 7
+- - - - - - -
+- - - - - - -
+- - - - - - -
+- - - - - - -
+- - - O - - -
+- - X X - - -";
+        assert_eq!(render(&after(&[3, 3, 2])), expected);
+    }
+
+    #[test]
+    fn ordering_tries_wins_and_blocks_first() {
+        // O threatens the bottom row, X can win in the second one.
+        let game = after(&[1, 1, 2, 2, 3, 3, 6, 4]);
+        assert_eq!(game.to_move, 'X');
+        let columns = ordered_columns(&game);
+        assert_eq!(columns[0], 0);
+        assert_eq!(columns[1], 4);
+        assert_eq!(&columns[2..], &[3, 2, 1, 5, 6]);
+    }
+
+    #[test]
+    fn search_takes_the_win_and_blocks() {
+        assert_eq!(search(&after(&[0, 6, 1, 6, 2, 5]), 4), vec![3]);
+        assert_eq!(search(&after(&[0, 6, 1, 6, 2]), 4), vec![3]);
+    }
+
+    #[test]
+    fn search_sees_a_double_threat() {
+        // X drops into the middle of the bottom row, leaving two ways to
+        // finish it that O can't both block.
+        let game = after(&[2, 2, 4, 4]);
+        let best = search(&game, 4);
+        assert_eq!(best, vec![3]);
+    }
+
+    #[test]
+    fn robot_beats_a_random_player() {
+        let robot = GravityRobot::new('X')
+            .with_difficulty(Difficulty::Medium)
+            .with_seed(1);
+        let random = RandomColumns(Random::new(2));
+        let outcome = play(
+            Gravity::default(),
+            [Box::new(robot), Box::new(random)],
+            &mut io::sink(),
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::Won('X'));
+    }
+
+    struct RandomColumns(Random);
+
+    impl VariantPlayer<Gravity> for RandomColumns {
+        fn sign(&self) -> char {
+            'O'
+        }
+
+        fn choose_move(&mut self, game: &Gravity) -> Result<Option<usize>, String> {
+            Ok(self.0.choose(&game.legal_moves()).cloned())
+        }
+    }
+}
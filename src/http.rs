@@ -0,0 +1,193 @@
+use std::io::{self, BufRead, Read, Write};
+
+const MAX_BODY_LENGTH: usize = 64 * 1024;
+const MAX_LINE_LENGTH: usize = 8 * 1024;
+const MAX_HEADERS: usize = 100;
+
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    pub fn json(status: u16, body: String) -> Response {
+        Response { status, body }
+    }
+
+    pub fn error(status: u16, message: &str) -> Response {
+        Response::json(status, format!("{{\"error\":{}}}", ::json::escape(message)))
+    }
+}
+
+pub fn read_request<R: BufRead>(reader: &mut R) -> io::Result<Request> {
+    let request_line = read_header_line(reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err(invalid_request("Malformed request line")),
+    };
+
+    let mut headers = Vec::new();
+    loop {
+        let line = read_header_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err(invalid_request("Too many headers"));
+        }
+        match line.find(':') {
+            Some(i) => headers.push((
+                line[..i].trim().to_string(),
+                line[i + 1..].trim().to_string(),
+            )),
+            None => return Err(invalid_request("Malformed header")),
+        }
+    }
+
+    let mut request = Request {
+        method,
+        path,
+        headers,
+        body: String::new(),
+    };
+
+    let length = match request.header("Content-Length") {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| invalid_request("Invalid Content-Length"))?,
+        None => 0,
+    };
+    if length > MAX_BODY_LENGTH {
+        return Err(invalid_request("The request body is too large"));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    request.body = String::from_utf8(body).map_err(|_| invalid_request("The body isn't UTF-8"))?;
+    Ok(request)
+}
+
+pub fn write_response<W: Write>(writer: &mut W, response: &Response) -> io::Result<()> {
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason_phrase(response.status),
+        response.body.len(),
+        response.body
+    )?;
+    writer.flush()
+}
+
+// Reads no more than `MAX_LINE_LENGTH` bytes, so a client can't make the
+// server keep an endless line in memory.
+fn read_header_line<R: BufRead>(reader: &mut R) -> io::Result<String> {
+    let mut line = String::new();
+    let length = reader
+        .take(MAX_LINE_LENGTH as u64 + 1)
+        .read_line(&mut line)?;
+    if length == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "The connection closed before the request was complete",
+        ));
+    }
+    if length > MAX_LINE_LENGTH {
+        return Err(invalid_request("A header line is too long"));
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn invalid_request(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        101 => "Switching Protocols",
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn reads_request_with_body() {
+        let raw = "POST /games/1/moves HTTP/1.1\r\nHost: x\r\ncontent-length: 13\r\n\r\n{\"x\":1,\"y\":2}";
+        let request = read_request(&mut Cursor::new(raw)).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/games/1/moves");
+        assert_eq!(request.header("Host"), Some("x"));
+        assert_eq!(request.body, "{\"x\":1,\"y\":2}");
+    }
+
+    #[test]
+    fn truncated_request_gives_error() {
+        let raw = "GET /games HTTP/1.1\r\n";
+        assert!(read_request(&mut Cursor::new(raw)).is_err());
+    }
+
+    #[test]
+    fn overlong_header_line_gives_error() {
+        let raw = format!(
+            "GET /games HTTP/1.1\r\nX: {}\r\n\r\n",
+            "a".repeat(MAX_LINE_LENGTH)
+        );
+        let error = read_request(&mut Cursor::new(raw)).unwrap_err();
+        assert_eq!(error.to_string(), "A header line is too long");
+    }
+
+    #[test]
+    fn too_many_headers_give_error() {
+        let raw = format!(
+            "GET /games HTTP/1.1\r\n{}\r\n",
+            "X: 1\r\n".repeat(MAX_HEADERS + 1)
+        );
+        let error = read_request(&mut Cursor::new(raw)).unwrap_err();
+        assert_eq!(error.to_string(), "Too many headers");
+
+        let raw = format!(
+            "GET /games HTTP/1.1\r\n{}\r\n",
+            "X: 1\r\n".repeat(MAX_HEADERS)
+        );
+        assert!(read_request(&mut Cursor::new(raw)).is_ok());
+    }
+
+    #[test]
+    fn writes_status_line_and_length() {
+        let mut output = Vec::new();
+        write_response(&mut output, &Response::json(201, "{}".to_string())).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("HTTP/1.1 201 Created\r\n"));
+        assert!(output.contains("Content-Length: 2\r\n"));
+        assert!(output.ends_with("\r\n\r\n{}"));
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(i64),
+    Str(String),
+}
+
+pub type Object = HashMap<String, Value>;
+
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// Only flat objects are needed by the API, so nested values are rejected.
+pub fn parse_object(input: &str) -> Result<Object, String> {
+    let mut chars = input.chars().peekable();
+    let mut object = Object::new();
+
+    skip_whitespace(&mut chars);
+    expect(&mut chars, '{')?;
+    skip_whitespace(&mut chars);
+
+    if chars.peek() == Some(&'}') {
+        chars.next();
+    } else {
+        loop {
+            skip_whitespace(&mut chars);
+            let key = parse_string(&mut chars)?;
+            skip_whitespace(&mut chars);
+            expect(&mut chars, ':')?;
+            skip_whitespace(&mut chars);
+            let value = parse_value(&mut chars)?;
+            object.insert(key, value);
+            skip_whitespace(&mut chars);
+
+            match chars.next() {
+                Some(',') => continue,
+                Some('}') => break,
+                _ => return Err("Expected ',' or '}' in object".to_string()),
+            }
+        }
+    }
+
+    skip_whitespace(&mut chars);
+    if chars.next().is_some() {
+        return Err("Unexpected characters after the object".to_string());
+    }
+    Ok(object)
+}
+
+fn parse_value(chars: &mut Peekable<Chars>) -> Result<Value, String> {
+    match chars.peek() {
+        Some(&'"') => parse_string(chars).map(Value::Str),
+        Some(&c) if c == '-' || c.is_ascii_digit() => parse_number(chars),
+        Some(&c) if c.is_alphabetic() => {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if !c.is_alphabetic() {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            match word.as_str() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                "null" => Ok(Value::Null),
+                _ => Err(format!("Unknown value '{}'", word)),
+            }
+        }
+        _ => Err("Expected a string, number, boolean or null".to_string()),
+    }
+}
+
+fn parse_number(chars: &mut Peekable<Chars>) -> Result<Value, String> {
+    let mut number = String::new();
+    while let Some(&c) = chars.peek() {
+        if c != '-' && !c.is_ascii_digit() {
+            break;
+        }
+        number.push(c);
+        chars.next();
+    }
+    number
+        .parse::<i64>()
+        .map(Value::Number)
+        .map_err(|_| format!("Invalid number '{}'", number))
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    expect(chars, '"')?;
+    let mut value = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(value),
+            Some('\\') => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('r') => value.push('\r'),
+                Some('t') => value.push('\t'),
+                Some('u') => {
+                    let code = chars.by_ref().take(4).collect::<String>();
+                    let c = u32::from_str_radix(&code, 16)
+                        .ok()
+                        .and_then(::std::char::from_u32)
+                        .ok_or_else(|| format!("Invalid escape '\\u{}'", code))?;
+                    value.push(c);
+                }
+                Some(c) => value.push(c),
+                None => return Err("Unterminated string".to_string()),
+            },
+            Some(c) => value.push(c),
+            None => return Err("Unterminated string".to_string()),
+        }
+    }
+}
+
+fn expect(chars: &mut Peekable<Chars>, expected: char) -> Result<(), String> {
+    match chars.next() {
+        Some(c) if c == expected => Ok(()),
+        _ => Err(format!("Expected '{}'", expected)),
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_flat_object() {
+        let object =
+            parse_object(r#"{ "x": 1, "name": "a \"b\"", "ok": true, "none": null }"#).unwrap();
+        assert_eq!(object["x"], Value::Number(1));
+        assert_eq!(object["name"], Value::Str("a \"b\"".to_string()));
+        assert_eq!(object["ok"], Value::Bool(true));
+        assert_eq!(object["none"], Value::Null);
+    }
+
+    #[test]
+    fn parses_empty_object() {
+        assert!(parse_object(" {} ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn nested_values_give_error() {
+        assert!(parse_object(r#"{"x": [1]}"#).is_err());
+    }
+
+    #[test]
+    fn trailing_characters_give_error() {
+        assert!(parse_object(r#"{"x": 1} x"#).is_err());
+    }
+
+    #[test]
+    fn escape_quotes_strings() {
+        assert_eq!(escape("say \"hi\"\n"), r#""say \"hi\"\n""#);
+    }
+}
@@ -0,0 +1,440 @@
+use board::Board;
+use players::{Action, Coordinate, Player};
+use random::Random;
+use robot_player::get_robot_coordinate;
+use rules::{Position, Rules};
+use stats;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use win_condition::winning_line;
+
+// A player that learns from its games, in the spirit of Donald Michie's
+// MENACE. Where MENACE kept a matchbox of beads for every position, this
+// keeps a value for every move in every position it has seen, and after
+// each game moves the values of the moves it played towards the result
+// (tabular Q-learning).
+
+// What a move it has never tried is worth, halfway between a loss and a win.
+const INITIAL: f64 = 0.5;
+const WIN: f64 = 1.0;
+const DRAW: f64 = 0.5;
+const LOSS: f64 = 0.0;
+
+pub const RATE: f64 = 0.2;
+pub const EXPLORATION: f64 = 0.1;
+
+// How many rows the learning curve has.
+const CURVE_POINTS: u32 = 20;
+
+// A played move: the position as its player saw it and the cell they took,
+// counted from 0 in reading order.
+type Step = (String, usize);
+
+// Positions are stored as seen by the player to move, with their own marks
+// as X, so what it learns going first also helps it going second.
+fn key(board: Board, sign: char) -> String {
+    board
+        .to_cells()
+        .chars()
+        .map(|cell| match cell {
+            '-' => '-',
+            c if c == sign => 'X',
+            _ => 'O',
+        })
+        .collect()
+}
+
+fn free_cells(key: &str) -> Vec<usize> {
+    key.char_indices()
+        .filter(|&(_, cell)| cell == '-')
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValueTable {
+    values: BTreeMap<String, [f64; 9]>,
+}
+
+impl ValueTable {
+    // The number of positions it has values for.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn value(&self, board: Board, sign: char, (x, y): Coordinate) -> f64 {
+        self.values(&key(board, sign))[y * 3 + x]
+    }
+
+    // The free cell with the highest value, the first one of equal values.
+    pub fn best_move(&self, board: Board, sign: char) -> Option<Coordinate> {
+        let key = key(board, sign);
+        let values = self.values(&key);
+        free_cells(&key)
+            .into_iter()
+            .rev()
+            .max_by(|&a, &b| values[a].total_cmp(&values[b]))
+            .map(|cell| (cell % 3, cell / 3))
+    }
+
+    fn values(&self, key: &str) -> [f64; 9] {
+        self.values.get(key).cloned().unwrap_or([INITIAL; 9])
+    }
+
+    // Works back from the last move, which is moved towards the result.
+    // Every earlier move is moved towards the best value of the player's
+    // next position.
+    fn learn(&mut self, steps: &[Step], reward: f64, rate: f64) {
+        let mut target = reward;
+        for &(ref key, cell) in steps.iter().rev() {
+            let mut values = self.values(key);
+            values[cell] += rate * (target - values[cell]);
+            self.values.insert(key.clone(), values);
+
+            target = free_cells(key)
+                .into_iter()
+                .map(|free| values[free])
+                .fold(f64::MIN, f64::max);
+        }
+    }
+
+    // A missing file just means nothing has been learned yet.
+    pub fn load(path: &Path) -> io::Result<ValueTable> {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                ValueTable::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(ValueTable::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_text())
+    }
+
+    pub fn parse(text: &str) -> Result<ValueTable, String> {
+        let mut table = ValueTable::default();
+        for (number, line) in text.lines().enumerate() {
+            let fields = line.split('\t').collect::<Vec<&str>>();
+            let invalid = || format!("Line {} of the value table is invalid", number + 1);
+            let valid_key = fields[0].len() == 9 && fields[0].chars().all(|c| "XO-".contains(c));
+            if fields.len() != 10 || !valid_key {
+                return Err(invalid());
+            }
+
+            let mut values = [INITIAL; 9];
+            for (value, field) in values.iter_mut().zip(fields[1..].iter()) {
+                *value = field.parse::<f64>().map_err(|_| invalid())?;
+            }
+            table.values.insert(fields[0].to_string(), values);
+        }
+        Ok(table)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (key, values) in self.values.iter() {
+            let values = values.iter().map(f64::to_string).collect::<Vec<String>>();
+            text.push_str(&format!("{}\t{}\n", key, values.join("\t")));
+        }
+        text
+    }
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    stats::default_path().map(|path| path.with_file_name("values.tsv"))
+}
+
+// Who the learner plays while training.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Opponent {
+    // Itself, learning from both sides of every game.
+    SelfPlay,
+    // The robot on hard, which never loses.
+    Robot,
+}
+
+impl Opponent {
+    pub fn from_name(name: &str) -> Option<Opponent> {
+        match name {
+            "self" => Some(Opponent::SelfPlay),
+            "robot" => Some(Opponent::Robot),
+            _ => None,
+        }
+    }
+}
+
+pub struct Learner {
+    pub table: ValueTable,
+    pub rate: f64,
+    // How often it tries a random move instead of the best one it knows.
+    pub exploration: f64,
+    random: Random,
+}
+
+impl Learner {
+    pub fn new(table: ValueTable) -> Learner {
+        Learner {
+            table,
+            rate: RATE,
+            exploration: EXPLORATION,
+            random: Random::from_time(),
+        }
+    }
+
+    pub fn with_rate(self, rate: f64) -> Self {
+        Learner { rate, ..self }
+    }
+
+    pub fn with_exploration(self, exploration: f64) -> Self {
+        Learner {
+            exploration,
+            ..self
+        }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        Learner {
+            random: Random::new(seed),
+            ..self
+        }
+    }
+
+    pub fn choose(&mut self, board: Board, sign: char) -> Option<Coordinate> {
+        let explore = (self.random.below(1_000_000) as f64) < self.exploration * 1_000_000.0;
+        if explore {
+            let free = free_cells(&key(board, sign));
+            self.random.choose(&free).map(|&cell| (cell % 3, cell / 3))
+        } else {
+            self.table.best_move(board, sign)
+        }
+    }
+
+    // Plays one game from the empty board, with X going first, and learns
+    // from it. Returns the winner.
+    pub fn play_episode(&mut self, opponent: Opponent, sign: char) -> Option<char> {
+        let mut position = Position::new(Board::new(), 'X');
+        let mut steps: BTreeMap<char, Vec<Step>> = BTreeMap::new();
+
+        while !position.is_over() {
+            let mover = position.to_move;
+            let square = if opponent == Opponent::Robot && mover != sign {
+                get_robot_coordinate(mover, position.board).expect("the robot has a move")
+            } else {
+                let square = self
+                    .choose(position.board, mover)
+                    .expect("an ongoing game has a free cell");
+                steps
+                    .entry(mover)
+                    .or_default()
+                    .push((key(position.board, mover), square.1 * 3 + square.0));
+                square
+            };
+            position = position.place(square).expect("free cells can be taken");
+        }
+
+        let winner = position.winner();
+        for (&mover, played) in steps.iter() {
+            self.table.learn(played, reward(winner, mover), self.rate);
+        }
+        winner
+    }
+
+    // Plays the episodes, switching sides every game, and writes the
+    // learning curve as CSV: the results of the learner over each stretch of
+    // games.
+    pub fn train(
+        &mut self,
+        opponent: Opponent,
+        episodes: u32,
+        output: &mut dyn Write,
+    ) -> io::Result<()> {
+        writeln!(
+            output,
+            "episode,wins,draws,losses,win_rate,draw_rate,loss_rate"
+        )?;
+        let stretch = (episodes / CURVE_POINTS).max(1);
+        let (mut wins, mut draws, mut losses) = (0, 0, 0);
+
+        for episode in 1..=episodes {
+            let sign = if episode % 2 == 1 { 'X' } else { 'O' };
+            match self.play_episode(opponent, sign) {
+                Some(winner) if winner == sign => wins += 1,
+                Some(_) => losses += 1,
+                None => draws += 1,
+            }
+
+            if episode % stretch == 0 || episode == episodes {
+                let games = f64::from(wins + draws + losses);
+                writeln!(
+                    output,
+                    "{},{},{},{},{:.3},{:.3},{:.3}",
+                    episode,
+                    wins,
+                    draws,
+                    losses,
+                    f64::from(wins) / games,
+                    f64::from(draws) / games,
+                    f64::from(losses) / games
+                )?;
+                wins = 0;
+                draws = 0;
+                losses = 0;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn reward(winner: Option<char>, sign: char) -> f64 {
+    match winner {
+        Some(winner) if winner == sign => WIN,
+        Some(_) => LOSS,
+        None => DRAW,
+    }
+}
+
+// The learner as a player in the main game. It keeps learning from the
+// games it plays.
+pub struct LearningPlayer {
+    pub sign: char,
+    pub learner: Learner,
+    steps: Vec<Step>,
+}
+
+impl LearningPlayer {
+    pub fn new(sign: char, learner: Learner) -> LearningPlayer {
+        LearningPlayer {
+            sign,
+            learner,
+            steps: Vec::new(),
+        }
+    }
+}
+
+impl Player for LearningPlayer {
+    fn sign(&self) -> char {
+        self.sign
+    }
+
+    fn choose_action(&mut self, board: Board) -> Result<Action, String> {
+        let square = self
+            .learner
+            .choose(board, self.sign)
+            .ok_or("No choice found")?;
+        self.steps
+            .push((key(board, self.sign), square.1 * 3 + square.0));
+        Ok(Action::Place(square))
+    }
+
+    fn game_over(&mut self, board: Board, _message: &str) {
+        let winner = winning_line(board).map(|line| board.grid[line[0].1][line[0].0]);
+        let reward = match winner {
+            // Games can end early, with a resignation or a player leaving.
+            None if board.to_cells().contains('-') => return self.steps.clear(),
+            winner => reward(winner, self.sign),
+        };
+        let steps = self.steps.split_off(0);
+        self.learner.table.learn(&steps, reward, self.learner.rate);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(rows: [&str; 3]) -> Board {
+        Board::from_cells(&rows.concat()).unwrap()
+    }
+
+    #[test]
+    fn positions_are_seen_from_the_player_to_move() {
+        let board = board(["XO-", "---", "---"]);
+        assert_eq!(key(board, 'X'), "XO-------");
+        assert_eq!(key(board, 'O'), "OX-------");
+        assert_eq!(free_cells("XO-----OX"), vec![2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn learning_moves_values_towards_the_result() {
+        let mut table = ValueTable::default();
+        let steps = vec![("---------".to_string(), 4), ("O---X----".to_string(), 2)];
+        table.learn(&steps, WIN, 0.5);
+        assert_eq!(table.values("O---X----")[2], 0.75);
+        // The first move is moved towards the best value after it.
+        assert_eq!(table.values("---------")[4], 0.625);
+        assert_eq!(table.best_move(Board::new(), 'X'), Some((1, 1)));
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn table_text_round_trips() {
+        let mut learner = Learner::new(ValueTable::default()).with_seed(3);
+        for _ in 0..20 {
+            learner.play_episode(Opponent::SelfPlay, 'X');
+        }
+        let text = learner.table.to_text();
+        assert_eq!(ValueTable::parse(&text), Ok(learner.table.clone()));
+        assert!(ValueTable::parse("XO-------\t0.5").is_err());
+        assert!(ValueTable::parse("XO-------\t1\t2\t3\t4\t5\t6\t7\t8\tnine").is_err());
+    }
+
+    #[test]
+    fn save_and_load_keep_what_was_learned() {
+        let path =
+            std::env::temp_dir().join(format!("tic_tac_toe_values_{}.tsv", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert_eq!(ValueTable::load(&path).unwrap(), ValueTable::default());
+
+        let mut learner = Learner::new(ValueTable::default()).with_seed(5);
+        learner.play_episode(Opponent::Robot, 'O');
+        learner.table.save(&path).unwrap();
+        let loaded = ValueTable::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, learner.table);
+    }
+
+    #[test]
+    fn learns_to_hold_the_robot_to_a_draw() {
+        let mut learner = Learner::new(ValueTable::default()).with_seed(7);
+        let mut curve = Vec::new();
+        learner.train(Opponent::Robot, 3000, &mut curve).unwrap();
+
+        let curve = String::from_utf8(curve).unwrap();
+        let rows = curve.lines().collect::<Vec<&str>>();
+        assert_eq!(
+            rows[0],
+            "episode,wins,draws,losses,win_rate,draw_rate,loss_rate"
+        );
+        assert_eq!(rows.len(), 21);
+        assert!(rows[20].starts_with("3000,0,"));
+
+        // Without exploring it has found a draw from both sides.
+        let mut learner = learner.with_exploration(0.0);
+        assert_eq!(learner.play_episode(Opponent::Robot, 'X'), None);
+        assert_eq!(learner.play_episode(Opponent::Robot, 'O'), None);
+    }
+
+    #[test]
+    fn player_learns_from_finished_games() {
+        let learner = Learner::new(ValueTable::default()).with_exploration(0.0);
+        let mut player = LearningPlayer::new('X', learner);
+        let start = board(["OO-", "XX-", "---"]);
+        assert_eq!(player.choose_action(start), Ok(Action::Place((2, 0))));
+
+        // Taking the top right corner lost to O's row.
+        player.game_over(board(["OOX", "XX-", "OOO"]), "");
+        assert!(player.learner.table.value(start, 'X', (2, 0)) < INITIAL);
+        assert_eq!(player.choose_action(start), Ok(Action::Place((2, 1))));
+    }
+}
@@ -0,0 +1,35 @@
+pub mod analysis;
+pub mod api;
+pub mod board;
+pub mod commands;
+pub mod game;
+pub mod gravity;
+pub mod http;
+pub mod json;
+pub mod learning;
+pub mod live;
+pub mod mcts;
+pub mod misere;
+pub mod morris;
+pub mod network;
+pub mod notakto;
+pub mod notation;
+pub mod order_chaos;
+pub mod players;
+pub mod quantum;
+pub mod qubic;
+pub mod random;
+pub mod render;
+pub mod robot_player;
+pub mod rules;
+pub mod series;
+pub mod server;
+pub mod stats;
+pub mod tablebase;
+pub mod tui;
+pub mod ultimate;
+pub mod user_input;
+pub mod variant;
+pub mod websocket;
+pub mod wild;
+pub mod win_condition;
@@ -0,0 +1,330 @@
+use api::{board_to_json, parse_move};
+use board::Board;
+use game::{Game, Observer};
+use http::{write_response, Request, Response};
+use json;
+use players::{Action, Coordinate, Player};
+use std::collections::BTreeMap;
+use std::io::{self, BufReader};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use websocket::{accept, Sender, WebSocket};
+
+// Clients connect to `/live/play` to take part in the next game, or to
+// `/live/watch` to receive every move and result of all running games.
+// Players answer each `turn` message with `{"x": 0, "y": 2}`.
+
+pub struct Live {
+    waiting: Mutex<Option<WebSocket>>,
+    feed: Arc<Mutex<Feed>>,
+}
+
+#[derive(Default)]
+struct Feed {
+    spectators: Vec<Sender>,
+    boards: BTreeMap<u64, Board>,
+    next_id: u64,
+}
+
+impl Feed {
+    fn broadcast(&mut self, message: &str) {
+        self.spectators
+            .retain_mut(|spectator| spectator.send_text(message).is_ok());
+    }
+}
+
+impl Default for Live {
+    fn default() -> Live {
+        Live::new()
+    }
+}
+
+impl Live {
+    pub fn new() -> Live {
+        Live {
+            waiting: Mutex::new(None),
+            feed: Arc::new(Mutex::new(Feed::default())),
+        }
+    }
+
+    pub fn join(&self, request: &Request, reader: BufReader<TcpStream>) -> io::Result<()> {
+        match request.path.as_str() {
+            "/live/play" => self.add_player(accept(request, reader)?),
+            "/live/watch" => self.add_spectator(accept(request, reader)?),
+            _ => write_response(&mut reader.into_inner(), &Response::error(404, "Not found")),
+        }
+    }
+
+    fn add_player(&self, mut socket: WebSocket) -> io::Result<()> {
+        let mut waiting = lock(&self.waiting);
+        // A player who left while waiting is dropped rather than paired.
+        match waiting.take() {
+            Some(opponent) if !opponent.is_closed() => {
+                start_game(Arc::clone(&self.feed), opponent, socket)
+            }
+            _ => {
+                socket.send_text(r#"{"type":"waiting"}"#)?;
+                *waiting = Some(socket);
+            }
+        }
+        Ok(())
+    }
+
+    fn add_spectator(&self, socket: WebSocket) -> io::Result<()> {
+        let mut feed = lock(&self.feed);
+        let games = feed
+            .boards
+            .iter()
+            .map(|(id, board)| format!(r#"{{"game":{},"board":{}}}"#, id, board_to_json(*board)))
+            .collect::<Vec<String>>();
+
+        let mut sender = socket.sender()?;
+        sender.send_text(&format!(
+            r#"{{"type":"state","games":[{}]}}"#,
+            games.join(",")
+        ))?;
+        feed.spectators.push(sender);
+        Ok(())
+    }
+}
+
+fn start_game(feed: Arc<Mutex<Feed>>, first: WebSocket, second: WebSocket) {
+    thread::spawn(move || {
+        let id = {
+            let mut feed = lock(&feed);
+            feed.next_id += 1;
+            let id = feed.next_id;
+            feed.boards.insert(id, Board::new());
+            id
+        };
+
+        let players = vec![first.sender(), second.sender()]
+            .into_iter()
+            .filter_map(Result::ok)
+            .collect();
+        let observer = FeedObserver {
+            id,
+            feed: Arc::clone(&feed),
+            players,
+        };
+
+        Game::new([
+            Box::new(WebSocketPlayer::new(id, 'O', first)),
+            Box::new(WebSocketPlayer::new(id, 'X', second)),
+        ])
+        .with_observer(Box::new(observer))
+        .with_output(Box::new(io::sink()))
+        .play();
+
+        lock(&feed).boards.remove(&id);
+    });
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+pub struct WebSocketPlayer {
+    sign: char,
+    socket: WebSocket,
+}
+
+impl WebSocketPlayer {
+    pub fn new(game: u64, sign: char, mut socket: WebSocket) -> WebSocketPlayer {
+        let _ = socket.send_text(&format!(
+            r#"{{"type":"welcome","game":{},"player":"{}"}}"#,
+            game, sign
+        ));
+        WebSocketPlayer { sign, socket }
+    }
+}
+
+impl Player for WebSocketPlayer {
+    fn sign(&self) -> char {
+        self.sign
+    }
+
+    fn choose_action(&mut self, board: Board) -> Result<Action, String> {
+        let turn = format!(
+            r#"{{"type":"turn","player":"{}","board":{}}}"#,
+            self.sign,
+            board_to_json(board)
+        );
+        if self.socket.send_text(&turn).is_err() {
+            return Ok(Action::Abandon);
+        }
+
+        let message = match self.socket.receive_text() {
+            Ok(Some(message)) => message,
+            Ok(None) | Err(_) => return Ok(Action::Abandon),
+        };
+
+        let sign = self.sign;
+        match parse_move(&message)
+            .and_then(|coordinate| board.add_value(coordinate, sign).map(|_| coordinate))
+        {
+            Ok(coordinate) => Ok(Action::Place(coordinate)),
+            Err(e) => {
+                let _ = self.socket.send_text(&format!(
+                    r#"{{"type":"error","message":{}}}"#,
+                    json::escape(&e)
+                ));
+                Err(e)
+            }
+        }
+    }
+}
+
+struct FeedObserver {
+    id: u64,
+    feed: Arc<Mutex<Feed>>,
+    players: Vec<Sender>,
+}
+
+impl FeedObserver {
+    fn publish(&mut self, message: &str) {
+        self.players
+            .retain_mut(|player| player.send_text(message).is_ok());
+        lock(&self.feed).broadcast(message);
+    }
+}
+
+impl Observer for FeedObserver {
+    fn on_move(&mut self, sign: char, coordinate: Coordinate, board: Board) {
+        lock(&self.feed).boards.insert(self.id, board);
+        self.publish(&format!(
+            r#"{{"type":"move","game":{},"player":"{}","x":{},"y":{},"board":{}}}"#,
+            self.id,
+            sign,
+            coordinate.0,
+            coordinate.1,
+            board_to_json(board)
+        ));
+    }
+
+    fn on_game_over(&mut self, board: Board, message: &str) {
+        self.publish(&format!(
+            r#"{{"type":"result","game":{},"message":{},"board":{}}}"#,
+            self.id,
+            json::escape(message),
+            board_to_json(board)
+        ));
+        for player in self.players.iter_mut() {
+            player.close();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::read_request;
+    use server;
+    use std::net::TcpListener;
+    use websocket::connect;
+
+    fn start_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || server::run(listener));
+        address
+    }
+
+    // Connects a player straight to `live`, returning the server's end of the
+    // connection too so a test can tell when a message has reached it.
+    fn join(live: &Live, listener: &TcpListener) -> (WebSocket, TcpStream) {
+        let address = listener.local_addr().unwrap().to_string();
+        let client = thread::spawn(move || connect(&address, "/live/play").unwrap());
+
+        let (stream, _) = listener.accept().unwrap();
+        let server_end = stream.try_clone().unwrap();
+        let mut reader = BufReader::new(stream);
+        let request = read_request(&mut reader).unwrap();
+        live.join(&request, reader).unwrap();
+        (client.join().unwrap(), server_end)
+    }
+
+    fn wait_for(socket: &mut WebSocket, kind: &str) -> String {
+        let tag = format!(r#""type":"{}""#, kind);
+        loop {
+            let message = socket.receive_text().unwrap().expect("connection closed");
+            if message.contains(&tag) {
+                return message;
+            }
+        }
+    }
+
+    fn play(socket: &mut WebSocket, x: usize, y: usize) {
+        wait_for(socket, "turn");
+        socket
+            .send_text(&format!(r#"{{"x":{},"y":{}}}"#, x, y))
+            .unwrap();
+    }
+
+    #[test]
+    fn spectator_follows_game_between_two_clients() {
+        let address = start_server();
+        let mut spectator = connect(&address, "/live/watch").unwrap();
+        wait_for(&mut spectator, "state");
+
+        let mut o = connect(&address, "/live/play").unwrap();
+        wait_for(&mut o, "waiting");
+        let mut x = connect(&address, "/live/play").unwrap();
+
+        play(&mut o, 0, 0);
+        play(&mut x, 1, 0);
+        play(&mut o, 0, 1);
+        play(&mut x, 1, 1);
+        play(&mut o, 0, 2);
+
+        let first_move = wait_for(&mut spectator, "move");
+        assert!(first_move.contains(r#""player":"O","x":0,"y":0"#));
+        let result = wait_for(&mut spectator, "result");
+        assert!(result.contains(r#""message":"Player O won!""#));
+        assert!(result.contains(r#""board":["OX-","OX-","O--"]"#));
+        assert!(wait_for(&mut x, "result").contains("Player O won!"));
+    }
+
+    #[test]
+    fn invalid_move_is_reported_to_player() {
+        let address = start_server();
+        let mut o = connect(&address, "/live/play").unwrap();
+        wait_for(&mut o, "waiting");
+        let mut x = connect(&address, "/live/play").unwrap();
+
+        play(&mut o, 1, 1);
+        play(&mut x, 1, 1);
+        let error = wait_for(&mut x, "error");
+        assert!(error.contains("The field is already taken"));
+
+        x.close();
+        assert!(wait_for(&mut o, "result").contains("Player X left the game"));
+    }
+
+    #[test]
+    fn player_who_left_while_waiting_is_not_paired() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let live = Live::new();
+        let (mut gone, server_end) = join(&live, &listener);
+        wait_for(&mut gone, "waiting");
+        gone.close();
+        // Blocks until the close frame has reached the server.
+        server_end.peek(&mut [0; 1]).unwrap();
+
+        let (mut o, _) = join(&live, &listener);
+        wait_for(&mut o, "waiting");
+        let (mut x, _) = join(&live, &listener);
+
+        play(&mut o, 1, 1);
+        assert!(wait_for(&mut x, "move").contains(r#""player":"O","x":1,"y":1"#));
+    }
+
+    #[test]
+    fn unknown_live_path_is_not_found() {
+        let address = start_server();
+        assert!(connect(&address, "/live/nothing").is_err());
+    }
+}
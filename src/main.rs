@@ -1,243 +1,702 @@
-mod board;
-mod players;
-mod robot_player;
-mod user_input;
-mod win_condition;
-
-use board::Board;
-use players::Player;
-use robot_player::get_robot_coordinate;
-use user_input::get_coordinate_from_user;
-use win_condition::is_winning_board;
+extern crate tic_tac_toe;
 
-fn main() {
-    let player_one = Player {
-        sign: 'O',
-        get_coordinate: get_coordinate_from_user,
-    };
+use std::env;
+use std::io;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process;
+use tic_tac_toe::analysis;
+use tic_tac_toe::board::Board;
+use tic_tac_toe::game::{Game, Observer, Outcome, RuleSet, RULE_SETS};
+use tic_tac_toe::gravity::{self, Gravity, GravityRobot};
+use tic_tac_toe::learning::{self, Learner, LearningPlayer, Opponent, ValueTable};
+use tic_tac_toe::mcts::MctsPlayer;
+use tic_tac_toe::morris::{Morris, MorrisRobot};
+use tic_tac_toe::network;
+use tic_tac_toe::notakto::{Notakto, NotaktoRobot};
+use tic_tac_toe::notation::{self, find_notation, Notation};
+use tic_tac_toe::order_chaos::{OrderChaos, OrderChaosRobot, Role};
+use tic_tac_toe::players::Player;
+use tic_tac_toe::quantum::{Quantum, QuantumRobot};
+use tic_tac_toe::qubic::{Cube, SearchPlayer};
+use tic_tac_toe::render::{find_theme, Charset, Layout, TextRenderer, THEMES};
+use tic_tac_toe::robot_player::{Difficulty, RobotPlayer, Strategy, DIFFICULTIES};
+use tic_tac_toe::series;
+use tic_tac_toe::stats::{self, Stats, StatsObserver};
+use tic_tac_toe::tui;
+use tic_tac_toe::ultimate::UltimateBoard;
+use tic_tac_toe::user_input::HumanPlayer;
+use tic_tac_toe::variant::{self, Variant, VariantHuman, VariantPlayer};
 
-    let player_two = Player {
-        sign: 'X',
-        get_coordinate: get_robot_coordinate,
-    };
+const USAGE: &str =
+    "Usage: tic_tac_toe [options] [serve [address] | connect <address> | tui | ultimate | qubic |
+                               notakto [boards] | order-chaos [order | chaos] | morris |
+                               gravity [columns rows] | quantum]
+       tic_tac_toe stats [show [name] | reset [name]]
+       tic_tac_toe train [episodes] [self | robot]
+       tic_tac_toe analyze <cells> [X | O]
 
-    println!("You are player '{}'", player_one.sign);
-    let game = Game::new((player_one, player_two));
+Options:
+    --theme <name>   Color theme for the board
+    --compact        Draw a smaller board
+    --ascii          Only use ASCII characters
+    --no-color       Never use colors
+    --best-of <n>    Play a match of up to n games against the robot
+    --difficulty <level>
+                     How well the robot plays: easy, medium or hard
+    --strategy <name>
+                     How the robot finds its moves on hard: heuristic,
+                     tablebase, learned from training or mcts; the
+                     last two only play standard rules
+    --iterations <n> How many random games the MCTS robot plays per move,
+                     50, 500 or 5000 by difficulty unless given; for
+                     --strategy mcts and ultimate
+    --seed <n>       Seed for the MCTS robot, to play the same game again
+    --rules <name>   standard, misere where three in a row loses, or wild
+                     where either player may place X or O
+    --name <name>    Whose statistics games against the robot and hosted
+                     network games count for
+    --notation <name>
+                     How moves are entered and shown: auto, coordinates,
+                     algebraic (b2), numpad (7 is top left) or cells
+                     (1 is top left)
+    --learning-rate <rate>
+                     How far training moves values towards each result,
+                     from 0 to 1
+    --exploration <rate>
+                     How often training tries a random move, from 0 to 1
+    --table <file>   Where training keeps what it has learned";
 
-    game.play();
+// What `--strategy` picks: how a `RobotPlayer` plays on hard, or one of
+// the robots that are players of their own.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Robot {
+    Rules(Strategy),
+    Learned,
+    Mcts,
 }
 
-struct Game {
-    board: Board,
-    players: (Player, Player),
-    current_player: Player,
-    is_over: bool,
-}
+const ROBOTS: [Robot; 4] = [
+    Robot::Rules(Strategy::Heuristic),
+    Robot::Rules(Strategy::Tablebase),
+    Robot::Learned,
+    Robot::Mcts,
+];
 
-impl Game {
-    fn new(players: (Player, Player)) -> Game {
-        let board = Board::new();
-        let current_player = players.0;
-        let players = players;
-        let is_over = false;
-        Game {
-            board,
-            current_player,
-            players,
-            is_over,
+impl Robot {
+    fn name(self) -> &'static str {
+        match self {
+            Robot::Rules(strategy) => strategy.name(),
+            Robot::Learned => "learned",
+            Robot::Mcts => "mcts",
         }
     }
 
-    fn next_turn(&self) -> Self {
-        Game {
-            board: self.board,
-            is_over: is_winning_board(self.board) || is_full_board(self.board),
-            players: self.players,
-            current_player: if self.current_player == self.players.0 {
-                self.players.1
-            } else {
-                self.players.0
-            },
-        }
+    fn from_name(name: &str) -> Option<Robot> {
+        ROBOTS.iter().cloned().find(|r| r.name() == name)
     }
+}
+
+struct Options {
+    command: Vec<String>,
+    renderer: TextRenderer,
+    notation: String,
+    games: usize,
+    difficulty: Difficulty,
+    strategy: Robot,
+    iterations: Option<u32>,
+    seed: Option<u64>,
+    rules: RuleSet,
+    name: String,
+    learning_rate: f64,
+    exploration: f64,
+    table: Option<PathBuf>,
+}
+
+impl Options {
+    fn notation(&self) -> Box<dyn Notation> {
+        find_notation(&self.notation).expect("the notation was checked when parsing options")
+    }
+}
+
+fn main() {
+    let options = match parse_options(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    let args = &options.command;
 
-    fn play(&self) {
-        if self.is_over {
-            println!("Game over");
-            println!("{}", self.get_final_message());
-        } else {
-            match (self.current_player.get_coordinate)(self.current_player.sign, self.board) {
-                Ok(coordinate) => self.place_choice(coordinate),
-                Err(e) => self.print_error_and_play(&e),
+    match args.first().map(String::as_str) {
+        None => play_against_robot(&options),
+        Some("serve") => {
+            let address = args.get(1).map_or(network::DEFAULT_ADDRESS, String::as_str);
+            serve(address, &options)
+        }
+        Some("connect") => match args.get(1) {
+            Some(address) => connect(address, &options),
+            None => exit_with_usage(),
+        },
+        Some("stats") => show_or_reset_stats(&args[1..]),
+        Some("analyze") => match (args.get(1), args.get(2).map(|s| s.to_uppercase())) {
+            (Some(cells), None) => analyze(cells, None),
+            (Some(cells), Some(ref sign)) if sign == "X" || sign == "O" => {
+                analyze(cells, sign.chars().next())
+            }
+            _ => exit_with_usage(),
+        },
+        Some("train") => {
+            let episodes = args
+                .get(1)
+                .map(|n| n.parse::<u32>().ok().filter(|&n| n > 0));
+            let opponent = args.get(2).map(|name| Opponent::from_name(name));
+            match (episodes, opponent) {
+                (None, None) => train(10_000, Opponent::SelfPlay, &options),
+                (Some(Some(episodes)), None) => train(episodes, Opponent::SelfPlay, &options),
+                (Some(Some(episodes)), Some(Some(opponent))) => train(episodes, opponent, &options),
+                _ => exit_with_usage(),
+            }
+        }
+        Some("ultimate") => play_ultimate(&options),
+        Some("qubic") => play_qubic(&options),
+        Some("notakto") => match args.get(1).map(|n| n.parse::<usize>()) {
+            None => play_notakto(3, &options),
+            Some(Ok(boards)) if (1..=9).contains(&boards) => play_notakto(boards, &options),
+            Some(_) => exit_with_usage(),
+        },
+        Some("order-chaos") => match args.get(1).map(|name| Role::from_name(name)) {
+            None => play_order_chaos(Role::Order, &options),
+            Some(Some(role)) => play_order_chaos(role, &options),
+            Some(None) => exit_with_usage(),
+        },
+        Some("morris") => play_morris(&options),
+        Some("quantum") => play_quantum(&options),
+        Some("gravity") => match (args.get(1), args.get(2)) {
+            (None, None) => play_gravity(gravity::COLUMNS, gravity::ROWS, &options),
+            (Some(columns), Some(rows)) => {
+                match (columns.parse::<usize>(), rows.parse::<usize>()) {
+                    (Ok(columns), Ok(rows))
+                        if (gravity::RUN..=9).contains(&columns)
+                            && (gravity::RUN..=9).contains(&rows) =>
+                    {
+                        play_gravity(columns, rows, &options)
+                    }
+                    _ => exit_with_usage(),
+                }
+            }
+            _ => exit_with_usage(),
+        },
+        Some("tui") => {
+            // The TUI robot always plays standard rules on hard.
+            if let Err(e) = tui::run(stats_observer(&options, 'O', "robot-hard")) {
+                eprintln!("Could not start the TUI: {}", e);
+                process::exit(1);
             }
         }
+        Some(_) => exit_with_usage(),
     }
+}
 
-    fn print_error_and_play(&self, error: &str) {
-        println!("{}", error);
-        println!("{}", self.board);
-        self.play();
-    }
+fn parse_options<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        command: Vec::new(),
+        renderer: TextRenderer::detect(),
+        notation: "auto".to_string(),
+        games: 1,
+        difficulty: Difficulty::Hard,
+        strategy: Robot::Rules(Strategy::Heuristic),
+        iterations: None,
+        seed: None,
+        rules: RuleSet::Standard,
+        name: stats::default_name(),
+        learning_rate: learning::RATE,
+        exploration: learning::EXPLORATION,
+        table: learning::default_path(),
+    };
 
-    fn place_choice(&self, coordinate: (usize, usize)) {
-        match self.board.add_value(coordinate, self.current_player.sign) {
-            Ok(b) => {
-                let new_game = Game { board: b, ..*self };
-                let new_game = new_game.next_turn();
-                println!(
-                    "\nBoard after player {}'s turn:\n",
-                    self.current_player.sign
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--theme" => {
+                let name = args.next().ok_or("--theme needs a name")?;
+                options.renderer.theme = find_theme(&name).ok_or_else(|| {
+                    let names = THEMES.iter().map(|t| t.name).collect::<Vec<&str>>();
+                    format!(
+                        "Unknown theme '{}', pick one of: {}",
+                        name,
+                        names.join(", ")
+                    )
+                })?;
+            }
+            "--notation" => {
+                let name = args.next().ok_or("--notation needs a name")?;
+                if find_notation(&name).is_none() {
+                    return Err(format!(
+                        "Unknown notation '{}', pick one of: {}",
+                        name,
+                        notation::NAMES.join(", ")
+                    ));
+                }
+                options.notation = name;
+            }
+            "--best-of" => {
+                options.games = args
+                    .next()
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .filter(|&n| n > 0)
+                    .ok_or("--best-of needs a number of games")?;
+            }
+            "--difficulty" => {
+                let name = args.next().ok_or("--difficulty needs a level")?;
+                options.difficulty = Difficulty::from_name(&name).ok_or_else(|| {
+                    let names = DIFFICULTIES.iter().map(|d| d.name()).collect::<Vec<&str>>();
+                    format!(
+                        "Unknown difficulty '{}', pick one of: {}",
+                        name,
+                        names.join(", ")
+                    )
+                })?;
+            }
+            "--strategy" => {
+                let name = args.next().ok_or("--strategy needs a name")?;
+                options.strategy = Robot::from_name(&name).ok_or_else(|| {
+                    let names = ROBOTS.iter().map(|r| r.name()).collect::<Vec<&str>>();
+                    format!(
+                        "Unknown strategy '{}', pick one of: {}",
+                        name,
+                        names.join(", ")
+                    )
+                })?;
+            }
+            "--iterations" => {
+                options.iterations = Some(
+                    args.next()
+                        .and_then(|n| n.parse::<u32>().ok())
+                        .filter(|&n| n > 0)
+                        .ok_or("--iterations needs a number of games")?,
+                );
+            }
+            "--seed" => {
+                options.seed = Some(
+                    args.next()
+                        .and_then(|n| n.parse::<u64>().ok())
+                        .ok_or("--seed needs a number")?,
                 );
-                println!("{}", new_game.board);
-                new_game.play();
             }
-            Err(e) => self.print_error_and_play(&e),
+            "--rules" => {
+                let name = args.next().ok_or("--rules needs a name")?;
+                options.rules = RuleSet::from_name(&name).ok_or_else(|| {
+                    let names = RULE_SETS.iter().map(|r| r.name()).collect::<Vec<&str>>();
+                    format!(
+                        "Unknown rules '{}', pick one of: {}",
+                        name,
+                        names.join(", ")
+                    )
+                })?;
+            }
+            "--name" => {
+                options.name = args
+                    .next()
+                    .filter(|name| stats::is_valid_name(name))
+                    .ok_or("--name needs a name without tabs")?;
+            }
+            "--learning-rate" => {
+                options.learning_rate = args
+                    .next()
+                    .and_then(|rate| rate.parse::<f64>().ok())
+                    .filter(|rate| (0.0..=1.0).contains(rate))
+                    .ok_or("--learning-rate needs a number from 0 to 1")?;
+            }
+            "--exploration" => {
+                options.exploration = args
+                    .next()
+                    .and_then(|rate| rate.parse::<f64>().ok())
+                    .filter(|rate| (0.0..=1.0).contains(rate))
+                    .ok_or("--exploration needs a number from 0 to 1")?;
+            }
+            "--table" => {
+                options.table = Some(PathBuf::from(args.next().ok_or("--table needs a file")?));
+            }
+            "--compact" => options.renderer.layout = Layout::Compact,
+            "--ascii" => options.renderer.charset = Charset::Ascii,
+            "--no-color" => options.renderer.color = false,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option '{}'", arg)),
+            _ => options.command.push(arg),
         }
     }
 
-    fn get_final_message(&self) -> String {
-        if is_winning_board(self.board) {
-            let winner = if self.current_player.sign == 'O' {
-                'X'
-            } else {
-                'O'
+    // Ultimate is always played by the MCTS robot.
+    let mcts = options.strategy == Robot::Mcts
+        || options.command.first().map(String::as_str) == Some("ultimate");
+    let standard_only = [Robot::Learned, Robot::Mcts];
+    if standard_only.contains(&options.strategy) && options.rules != RuleSet::Standard {
+        return Err(format!(
+            "The {} strategy only plays standard rules, not {}",
+            options.strategy.name(),
+            options.rules.name()
+        ));
+    }
+    if !mcts && (options.iterations.is_some() || options.seed.is_some()) {
+        return Err("--iterations and --seed need --strategy mcts or ultimate".to_string());
+    }
+    if options.strategy == Robot::Rules(Strategy::Tablebase)
+        && options.difficulty != Difficulty::Hard
+    {
+        return Err("The tablebase strategy only plays on hard".to_string());
+    }
+    Ok(options)
+}
+
+fn play_against_robot(options: &Options) {
+    let player_one = HumanPlayer::new('O')
+        .with_notation(options.notation())
+        .with_rules(options.rules);
+    let (player_two, opponent): (Box<dyn Player>, String) = match (options.strategy, options.rules)
+    {
+        // Plays what the table holds without changing it, as the game is
+        // never saved. Only standard rules get this far.
+        (Robot::Learned, _) => {
+            let learner = Learner::new(load_table(options))
+                .with_rate(0.0)
+                .with_exploration(0.0);
+            (
+                Box::new(LearningPlayer::new('X', learner)),
+                "robot-learned".to_string(),
+            )
+        }
+        (Robot::Mcts, _) => (
+            Box::new(mcts_player('X', options)),
+            "robot-mcts".to_string(),
+        ),
+        (Robot::Rules(strategy), rules) => {
+            let robot = RobotPlayer::new('X')
+                .with_difficulty(options.difficulty)
+                .with_strategy(strategy)
+                .with_rules(rules);
+            let opponent = match rules {
+                RuleSet::Standard => format!("robot-{}", options.difficulty.name()),
+                rules => format!("robot-{}-{}", options.difficulty.name(), rules.name()),
             };
-            format!("Player {} won!", winner)
-        } else {
-            "It's a tie!".to_string()
+            (Box::new(robot), opponent)
         }
+    };
+
+    println!("You are player '{}'", player_one.sign);
+    let players: [Box<dyn Player>; 2] = [Box::new(player_one), player_two];
+    let stats = stats_observer(options, 'O', &opponent);
+    let setup = |game: Game| {
+        let game = game
+            .with_renderer(Box::new(options.renderer.clone()))
+            .with_notation(options.notation())
+            .with_rules(options.rules);
+        match stats {
+            Some(ref stats) => game.with_observer(Box::new(stats.clone())),
+            None => game,
+        }
+    };
+
+    if options.games > 1 {
+        series::play(players, options.games, &mut io::stdout(), setup);
+    } else {
+        setup(Game::new(players)).play();
     }
 }
 
-fn is_full_board(board: Board) -> bool {
-    !board
-        .grid
-        .iter()
-        .flat_map(|r| r.iter())
-        .collect::<Vec<&char>>()
-        .contains(&&'-')
+// Plays fewer random games on easier difficulties.
+fn mcts_player(sign: char, options: &Options) -> MctsPlayer {
+    let iterations = options.iterations.unwrap_or(match options.difficulty {
+        Difficulty::Easy => 50,
+        Difficulty::Medium => 500,
+        Difficulty::Hard => 5000,
+    });
+    let robot = MctsPlayer::new(sign, iterations);
+    match options.seed {
+        Some(seed) => robot.with_seed(seed),
+        None => robot,
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use board::Board;
+fn play_ultimate(options: &Options) {
+    let robot = mcts_player('O', options);
+    play_variant(
+        UltimateBoard::new(),
+        'X',
+        Box::new(robot),
+        "ultimate",
+        options,
+    );
+}
 
-    fn dummy_get_coordinate(_sign: char, _board: Board) -> Result<(usize, usize), String> {
-        Err("Not implemented".to_string())
+fn play_qubic(options: &Options) {
+    let depth = match options.difficulty {
+        Difficulty::Easy => 1,
+        Difficulty::Medium => 2,
+        Difficulty::Hard => 3,
+    };
+    let robot = SearchPlayer::new('O', depth);
+    play_variant(Cube::new(), 'X', Box::new(robot), "qubic", options);
+}
+
+fn play_notakto(boards: usize, options: &Options) {
+    let robot = NotaktoRobot::new('2').with_difficulty(options.difficulty);
+    play_variant(
+        Notakto::new(boards),
+        '1',
+        Box::new(robot),
+        "notakto",
+        options,
+    );
+}
+
+fn play_order_chaos(role: Role, options: &Options) {
+    let robot = OrderChaosRobot::new(role.other()).with_difficulty(options.difficulty);
+    play_variant(
+        OrderChaos::new(),
+        role.sign(),
+        Box::new(robot),
+        "order-chaos",
+        options,
+    );
+}
+
+fn play_morris(options: &Options) {
+    let robot = MorrisRobot::new('O').with_difficulty(options.difficulty);
+    play_variant(Morris::new(), 'X', Box::new(robot), "morris", options);
+}
+
+fn play_gravity(columns: usize, rows: usize, options: &Options) {
+    let robot = GravityRobot::new('O').with_difficulty(options.difficulty);
+    play_variant(
+        Gravity::new(columns, rows),
+        'X',
+        Box::new(robot),
+        "gravity",
+        options,
+    );
+}
+
+fn play_quantum(options: &Options) {
+    let robot = QuantumRobot::new('O').with_difficulty(options.difficulty);
+    play_variant(Quantum::new(), 'X', Box::new(robot), "quantum", options);
+}
+
+// The human plays `sign` at the terminal against the robot.
+fn play_variant<R: Variant>(
+    start: R,
+    sign: char,
+    robot: Box<dyn VariantPlayer<R>>,
+    variant: &str,
+    options: &Options,
+) {
+    println!("You are {}", start.player_name(sign));
+    let human = VariantHuman::new(sign, Box::new(io::stdin().lock()), Box::new(io::stdout()));
+    let outcome = match variant::play(start, [Box::new(human), robot], &mut io::stdout()) {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            eprintln!("Could not write the board: {}", e);
+            process::exit(1);
+        }
+    };
+    record_outcome(options, sign, outcome, variant);
+}
+
+// Keeps learning from the saved table, if there is one, and saves it again.
+// The learning curve goes to the standard output, so it can be redirected
+// to a CSV file.
+fn train(episodes: u32, opponent: Opponent, options: &Options) {
+    let mut learner = Learner::new(load_table(options))
+        .with_rate(options.learning_rate)
+        .with_exploration(options.exploration);
+
+    if let Err(e) = learner.train(opponent, episodes, &mut io::stdout()) {
+        eprintln!("Could not write the learning curve: {}", e);
+        process::exit(1);
     }
 
-    #[test]
-    fn full_board_is_full() {
-        let board = Board {
-            grid: [['X'; 3], ['X'; 3], ['X'; 3]],
-        };
-        assert!(is_full_board(board));
+    match options.table {
+        Some(ref path) => match learner.table.save(path) {
+            Ok(()) => eprintln!(
+                "Saved values for {} positions to {}",
+                learner.table.len(),
+                path.display()
+            ),
+            Err(e) => {
+                eprintln!("Could not save {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        },
+        None => eprintln!("Nowhere to save the values, pass --table <file>"),
     }
+}
 
-    #[test]
-    fn empty_board_is_not_full() {
-        let board = Board {
-            grid: [['-'; 3], ['-'; 3], ['-'; 3]],
-        };
-        assert!(!is_full_board(board));
+// Nothing has been learned yet when there is no table to read.
+fn load_table(options: &Options) -> ValueTable {
+    match options.table {
+        Some(ref path) => ValueTable::load(path).unwrap_or_else(|e| {
+            eprintln!("Could not read {}: {}", path.display(), e);
+            process::exit(1);
+        }),
+        None => ValueTable::default(),
     }
+}
 
-    #[test]
-    fn slightly_populated_board_is_not_full() {
-        let board = Board {
-            grid: [['-', 'X', 'O'], ['-', '-', '-'], ['O', '-', 'X']],
-        };
-        assert!(!is_full_board(board));
+// Counts a game of one of the variants with their own play loop against the
+// robot in the statistics.
+fn record_outcome(options: &Options, sign: char, outcome: Outcome, variant: &str) {
+    let opponent = format!("robot-{}-{}", options.difficulty.name(), variant);
+    if let Some(mut observer) = stats_observer(options, sign, &opponent) {
+        observer.on_outcome(outcome);
     }
+}
 
-    #[test]
-    fn add_value_in_empty_field_adds_value() {
-        let sign = 'X';
-        let board = Board::new();
-        let player = Player {
-            sign,
-            get_coordinate: dummy_get_coordinate,
-        };
-        let result_board = board.add_value((0, 0), player.sign).unwrap();
-        assert_eq!(result_board.grid[0][0], sign);
+// Nothing is recorded when there is no directory to keep the statistics in.
+fn stats_observer(options: &Options, sign: char, opponent: &str) -> Option<StatsObserver> {
+    stats::default_path().map(|path| StatsObserver {
+        path,
+        name: options.name.clone(),
+        sign,
+        opponent: opponent.to_string(),
+    })
+}
+
+// Takes the cells in reading order with - for empty ones, optionally split
+// into rows with slashes, like XO-/-X-/--O.
+fn analyze(cells: &str, to_move: Option<char>) {
+    let cells = cells.to_uppercase().replace('/', "");
+    let report = Board::from_cells(&cells).and_then(|board| {
+        analysis::analyze(
+            board,
+            to_move.unwrap_or_else(|| analysis::player_to_move(board)),
+        )
+    });
+    match report {
+        Ok(report) => println!("{}", report),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
     }
+}
 
-    #[test]
-    fn add_value_with_custom_sign_uses_sign() {
-        let sign = 'A';
-        let board = Board::new();
-        let player = Player {
-            sign,
-            get_coordinate: dummy_get_coordinate,
-        };
-        let result_board = board.add_value((0, 0), player.sign).unwrap();
-        assert_eq!(result_board.grid[0][0], sign);
+fn serve(address: &str, options: &Options) {
+    let result = TcpListener::bind(address).and_then(|listener| {
+        println!("You are player 'O'");
+        network::serve(
+            &listener,
+            Box::new(HumanPlayer::new('O').with_notation(options.notation())),
+            Box::new(options.renderer.clone()),
+        )
+    });
+
+    match result {
+        // Only the host has the whole game, so the player who connects
+        // keeps no statistics.
+        Ok(game) => {
+            if let Some(mut observer) = stats_observer(options, 'O', "network") {
+                observer.on_outcome(game.outcome());
+            }
+        }
+        Err(e) => {
+            eprintln!("Could not host the game: {}", e);
+            process::exit(1);
+        }
     }
+}
 
-    #[test]
-    fn add_value_outside_of_bounds_is_invalid() {
-        let board = Board::new();
+fn connect(address: &str, options: &Options) {
+    let result = network::connect(address, &options.renderer, |sign| {
+        Box::new(HumanPlayer::new(sign).with_notation(options.notation())) as Box<dyn Player>
+    });
 
-        let player = Player {
-            sign: 'X',
-            get_coordinate: dummy_get_coordinate,
-        };
+    if let Err(e) = result {
+        eprintln!("Lost the connection to {}: {}", address, e);
+        process::exit(1);
+    }
+}
 
-        let result = board.add_value((3, 3), player.sign);
-        assert_eq!(result.is_err(), true);
+fn show_or_reset_stats(args: &[String]) {
+    let path = match stats::default_path() {
+        Some(path) => path,
+        None => {
+            eprintln!("Could not find a directory to keep the statistics in");
+            process::exit(1);
+        }
+    };
+    let mut stats = match Stats::load(&path) {
+        Ok(stats) => stats,
+        Err(e) => {
+            eprintln!("Could not read the statistics in {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    };
+
+    let name = args.get(1).map(String::as_str);
+    match args.first().map(String::as_str) {
+        None | Some("show") if args.len() <= 2 => println!("{}", stats.report(name)),
+        Some("reset") if args.len() <= 2 => {
+            if !stats.reset(name) {
+                println!("There were no statistics to reset");
+            } else if let Err(e) = stats.save(&path) {
+                eprintln!("Could not reset the statistics: {}", e);
+                process::exit(1);
+            } else {
+                println!("The statistics have been reset");
+            }
+        }
+        _ => exit_with_usage(),
+    }
+}
+
+fn exit_with_usage() {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Options, String> {
+        parse_options(args.split_whitespace().map(String::from))
     }
 
     #[test]
-    fn add_value_to_existing_field_is_invalid() {
-        let board = Board {
-            grid: [['X'; 3], ['-'; 3], ['-'; 3]],
-        };
+    fn mcts_strategy_only_plays_standard_rules() {
+        assert!(parse("--strategy mcts").is_ok());
+        assert!(parse("--strategy mcts --rules misere").is_err());
+        assert!(parse("--rules wild --strategy mcts").is_err());
+    }
 
-        let player = Player {
-            sign: 'X',
-            get_coordinate: dummy_get_coordinate,
-        };
+    #[test]
+    fn learned_strategy_only_plays_standard_rules() {
+        assert!(parse("--strategy learned").is_ok());
+        assert!(parse("--strategy learned --rules misere").is_err());
+        assert!(parse("--rules wild --strategy learned").is_err());
+    }
 
-        let result = board.add_value((0, 0), player.sign);
-        assert_eq!(result.is_err(), true);
+    #[test]
+    fn tablebase_strategy_only_plays_on_hard() {
+        assert!(parse("--strategy tablebase").is_ok());
+        assert!(parse("--strategy tablebase --difficulty easy").is_err());
+        assert!(parse("--difficulty medium --strategy tablebase").is_err());
     }
 
     #[test]
-    fn play_game_with_game_over_does_not_panic() {
-        let players = (
-            Player {
-                sign: 'O',
-                get_coordinate: dummy_get_coordinate,
-            },
-            Player {
-                sign: 'X',
-                get_coordinate: dummy_get_coordinate,
-            },
-        );
-        let mut game = Game::new(players);
-        game.is_over = true;
-
-        game.play();
+    fn every_strategy_can_be_named() {
+        for &robot in ROBOTS.iter() {
+            assert_eq!(Robot::from_name(robot.name()), Some(robot));
+        }
     }
 
     #[test]
-    fn next_turn_switches_current_player() {
-        let players = (
-            Player {
-                sign: 'O',
-                get_coordinate: dummy_get_coordinate,
-            },
-            Player {
-                sign: 'X',
-                get_coordinate: dummy_get_coordinate,
-            },
-        );
-        let game = Game::new(players);
-        let initial_player = game.current_player;
-
-        let game_after_turn = game.next_turn();
-        let players_are_same = initial_player == game_after_turn.current_player;
-        assert_eq!(players_are_same, false)
+    fn iterations_and_seed_need_the_mcts_robot() {
+        let options = parse("--strategy mcts --iterations 100 --seed 7").unwrap();
+        assert_eq!((options.iterations, options.seed), (Some(100), Some(7)));
+        assert!(parse("--seed 7 ultimate").is_ok());
+
+        assert!(parse("--iterations 100").is_err());
+        assert!(parse("--strategy heuristic --seed 7").is_err());
+        assert!(parse("--seed 7 qubic").is_err());
     }
 }
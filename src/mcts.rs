@@ -0,0 +1,275 @@
+use board::Board;
+use players::{Action, Player};
+use random::Random;
+use rules::{Position, Rules};
+use std::time::{Duration, Instant};
+use variant::{Variant, VariantPlayer};
+
+// Monte Carlo tree search with UCT. It needs nothing but the rules of the
+// game: it plays out many random games and prefers the moves that win most
+// of them, so it works for the variants without hand-written heuristics
+// like `fork_move`.
+
+// Random games that go on this long count as draws, for games like three
+// men's morris that can go on forever.
+const MAX_PLAYOUT: usize = 500;
+
+// How long to keep searching for each move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Budget {
+    Iterations(u32),
+    Time(Duration),
+}
+
+pub struct MctsPlayer {
+    sign: char,
+    budget: Budget,
+    random: Random,
+}
+
+impl MctsPlayer {
+    pub fn new(sign: char, iterations: u32) -> MctsPlayer {
+        MctsPlayer {
+            sign,
+            budget: Budget::Iterations(iterations),
+            random: Random::from_time(),
+        }
+    }
+
+    pub fn with_time_limit(self, limit: Duration) -> Self {
+        MctsPlayer {
+            budget: Budget::Time(limit),
+            ..self
+        }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        MctsPlayer {
+            random: Random::new(seed),
+            ..self
+        }
+    }
+
+    pub fn choose<R: Rules>(&mut self, position: &R) -> Result<R::Move, String> {
+        search(position, self.budget, &mut self.random).ok_or_else(|| "No choice found".to_string())
+    }
+}
+
+// Plays standard tic-tac-toe in the main game.
+impl Player for MctsPlayer {
+    fn sign(&self) -> char {
+        self.sign
+    }
+
+    fn choose_action(&mut self, board: Board) -> Result<Action, String> {
+        self.choose(&Position::new(board, self.sign))
+            .map(|chosen| Action::Place(chosen.coordinate))
+    }
+}
+
+// Plays any of the variants.
+impl<R: Variant> VariantPlayer<R> for MctsPlayer {
+    fn sign(&self) -> char {
+        self.sign
+    }
+
+    fn choose_move(&mut self, position: &R) -> Result<Option<R::Move>, String> {
+        self.choose(position).map(Some)
+    }
+}
+
+struct Node<R: Rules> {
+    position: R,
+    parent: Option<usize>,
+    last_move: Option<R::Move>,
+    // Who made `last_move`. Not always the opposite of who is to move now,
+    // since in some games a player moves twice in a row.
+    mover: char,
+    children: Vec<usize>,
+    untried: Vec<R::Move>,
+    visits: f64,
+    // Won games, counted for `mover`.
+    score: f64,
+}
+
+impl<R: Rules> Node<R> {
+    fn new(position: R, parent: Option<usize>, last_move: Option<R::Move>, mover: char) -> Node<R> {
+        Node {
+            untried: position.legal_moves(),
+            position,
+            parent,
+            last_move,
+            mover,
+            children: Vec::new(),
+            visits: 0.0,
+            score: 0.0,
+        }
+    }
+}
+
+// The most visited move after searching for the budget.
+pub fn search<R: Rules>(position: &R, budget: Budget, random: &mut Random) -> Option<R::Move> {
+    let started = Instant::now();
+    let mut nodes = vec![Node::new(position.clone(), None, None, position.to_move())];
+    let mut iterations = 0;
+
+    loop {
+        let done = match budget {
+            Budget::Iterations(limit) => iterations >= limit.max(1),
+            Budget::Time(limit) => iterations > 0 && started.elapsed() >= limit,
+        };
+        if done {
+            break;
+        }
+        iterations += 1;
+
+        // Selection
+        let mut current = 0;
+        while nodes[current].untried.is_empty() && !nodes[current].children.is_empty() {
+            let parent_visits = nodes[current].visits;
+            current = *nodes[current]
+                .children
+                .iter()
+                .max_by(|&&a, &&b| {
+                    let uct = |node: &Node<R>| {
+                        node.score / node.visits + (2.0 * parent_visits.ln() / node.visits).sqrt()
+                    };
+                    uct(&nodes[a]).total_cmp(&uct(&nodes[b]))
+                })
+                .expect("the node has children");
+        }
+
+        // Expansion
+        if !nodes[current].untried.is_empty() {
+            let index = random.below(nodes[current].untried.len());
+            let chosen = nodes[current].untried.swap_remove(index);
+            let mover = nodes[current].position.to_move();
+            let position = nodes[current]
+                .position
+                .play(chosen)
+                .expect("untried moves are legal");
+            nodes.push(Node::new(position, Some(current), Some(chosen), mover));
+            let child = nodes.len() - 1;
+            nodes[current].children.push(child);
+            current = child;
+        }
+
+        // Simulation
+        let mut position = nodes[current].position.clone();
+        for _ in 0..MAX_PLAYOUT {
+            let moves = position.legal_moves();
+            match random.choose(&moves) {
+                Some(&chosen) => position = position.play(chosen).expect("the move is legal"),
+                None => break,
+            }
+        }
+        let winner = position.winner();
+
+        // Backpropagation
+        let mut node = Some(current);
+        while let Some(index) = node {
+            nodes[index].visits += 1.0;
+            nodes[index].score += match winner {
+                Some(sign) if sign == nodes[index].mover => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+            node = nodes[index].parent;
+        }
+    }
+
+    nodes[0]
+        .children
+        .iter()
+        .max_by(|&&a, &&b| nodes[a].visits.total_cmp(&nodes[b].visits))
+        .and_then(|&child| nodes[child].last_move)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gravity::Gravity;
+    use players::Move;
+    use robot_player::get_robot_coordinate;
+    use rules::Value;
+    use tablebase;
+
+    // Whether the move keeps a draw or better for the player who makes it.
+    fn holds(position: &Position, chosen: Move) -> bool {
+        let next = position.play(chosen).unwrap();
+        let value = tablebase::lookup(next.board, next.to_move).unwrap().value;
+        !matches!(value.before(), Value::Loss(_))
+    }
+
+    // The search is random, so it's only held to never giving away a
+    // position the tablebase says can be held, in every position that can
+    // come up for either player. Positions where no move loses are skipped
+    // to keep the test quick.
+    #[test]
+    fn never_loses_a_position_the_tablebase_holds() {
+        for reached in tablebase::reachable() {
+            for &sign in ['X', 'O'].iter() {
+                let position = Position::new(reached.board, sign);
+                match tablebase::lookup(position.board, sign) {
+                    Some(ref entry) if !entry.moves.is_empty() => {
+                        if let Value::Loss(_) = entry.value {
+                            continue;
+                        }
+                    }
+                    _ => continue,
+                }
+                let moves = position.legal_moves();
+                if moves.iter().all(|&chosen| holds(&position, chosen)) {
+                    continue;
+                }
+
+                let chosen = MctsPlayer::new(sign, 3000)
+                    .with_seed(1)
+                    .choose(&position)
+                    .unwrap();
+                assert!(
+                    holds(&position, chosen),
+                    "{} playing {:?} on {} loses",
+                    sign,
+                    chosen.coordinate,
+                    position.board.to_cells()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn agrees_with_the_perfect_robot_on_forced_moves() {
+        // The top right corner both blocks O and completes X's diagonal.
+        let grid = [['O', 'O', '-'], ['-', 'X', '-'], ['X', '-', '-']];
+        let board = Board { grid };
+        let chosen = search(
+            &Position::new(board, 'X'),
+            Budget::Iterations(2000),
+            &mut Random::new(3),
+        )
+        .map(|chosen| chosen.coordinate);
+        assert_eq!(chosen, Some((2, 0)));
+        assert_eq!(chosen, get_robot_coordinate('X', board).ok());
+    }
+
+    #[test]
+    fn time_budget_still_searches() {
+        let mut mcts = MctsPlayer::new('X', 0)
+            .with_time_limit(Duration::from_millis(20))
+            .with_seed(1);
+        assert!(mcts.choose(&Position::new(Board::new(), 'X')).is_ok());
+    }
+
+    #[test]
+    fn finds_the_winning_column() {
+        let game = [0, 6, 1, 6, 2]
+            .iter()
+            .fold(Gravity::default(), |game, &column| {
+                game.play(column).unwrap()
+            });
+        let game = game.play(5).unwrap();
+        let chosen = search(&game, Budget::Iterations(500), &mut Random::new(2));
+        assert_eq!(chosen, Some(3));
+    }
+}
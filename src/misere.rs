@@ -0,0 +1,70 @@
+use board::Board;
+use game::RuleSet;
+use players::Coordinate;
+use rules::{negamax, negamax_move, Position};
+
+// In misère tic-tac-toe whoever completes three in a row loses. The
+// heuristics of the standard robot don't carry over, so this searches the
+// whole game tree instead.
+
+// The best move for `sign`, preferring quick wins and slow losses.
+pub fn misere_coordinate(sign: char, board: Board) -> Result<Coordinate, String> {
+    negamax_move(&position(board, sign))
+        .map(|chosen| chosen.coordinate)
+        .ok_or_else(|| "No choice found".to_string())
+}
+
+// How good the position is for `to_move` with perfect play: positive when
+// they win, negative when they lose and zero for a draw. Wins that come
+// sooner score higher.
+pub fn misere_value(board: Board, to_move: char) -> i32 {
+    negamax(&position(board, to_move))
+}
+
+fn position(board: Board, to_move: char) -> Position {
+    Position::new(board, to_move).with_rules(RuleSet::Misere)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::Rules;
+
+    #[test]
+    fn empty_board_is_a_draw() {
+        assert_eq!(misere_value(Board::new(), 'X'), 0);
+    }
+
+    #[test]
+    fn center_is_the_only_safe_opening() {
+        let start = position(Board::new(), 'X');
+        for chosen in start.legal_moves() {
+            let score = -negamax(&start.play(chosen).unwrap());
+            if chosen.coordinate == (1, 1) {
+                assert_eq!(score, 0);
+            } else {
+                assert!(score < 0, "{:?} should lose", chosen.coordinate);
+            }
+        }
+        assert_eq!(misere_coordinate('X', Board::new()), Ok((1, 1)));
+    }
+
+    #[test]
+    fn avoids_completing_a_line() {
+        let grid = [['X', 'X', '-'], ['O', '-', 'O'], ['-', 'O', 'X']];
+        assert_eq!(misere_coordinate('X', Board { grid }), Ok((0, 2)));
+    }
+
+    #[test]
+    fn forced_to_complete_a_line_loses() {
+        let grid = [['X', 'O', 'X'], ['O', 'X', 'O'], ['O', 'X', '-']];
+        assert!(misere_value(Board { grid }, 'X') < 0);
+    }
+
+    #[test]
+    fn mirrors_the_opponent_after_taking_the_center() {
+        let grid = [['O', '-', '-'], ['-', 'X', '-'], ['-', '-', '-']];
+        assert_eq!(misere_value(Board { grid }, 'X'), 0);
+        assert_eq!(misere_coordinate('X', Board { grid }), Ok((2, 2)));
+    }
+}
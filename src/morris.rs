@@ -0,0 +1,546 @@
+use board::Board;
+use notation::{CellNumbers, Notation};
+use players::Coordinate;
+use random::Random;
+use robot_player::{get_opponent_sign, Difficulty};
+use rules::{Rules, Status, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::OnceLock;
+use variant::{Variant, VariantPlayer};
+use win_condition::winning_line;
+
+// Three men's morris starts like tic-tac-toe, but each player only has three
+// pieces. Once they are all on the board, a turn slides one of them to an
+// adjacent empty cell instead: along a row or column, or along a diagonal
+// to or from the center. Three in a row wins, a player who can't move loses
+// and a position coming up for the third time is a draw.
+//
+// Cells are numbered 1 to 9 in reading order, so sliding from the top left
+// corner to the center is `15`.
+
+pub const PIECES: usize = 3;
+const REPETITIONS: usize = 3;
+
+type Key = ([[char; 3]; 3], char);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Move {
+    Place(Coordinate),
+    Slide(Coordinate, Coordinate),
+}
+
+pub fn is_adjacent(from: Coordinate, to: Coordinate) -> bool {
+    let dx = from.0.abs_diff(to.0);
+    let dy = from.1.abs_diff(to.1);
+    let through_center = from == (1, 1) || to == (1, 1);
+    dx + dy == 1 || (dx == 1 && dy == 1 && through_center)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Morris {
+    pub board: Board,
+    pub to_move: char,
+}
+
+impl Default for Morris {
+    fn default() -> Morris {
+        Morris::new()
+    }
+}
+
+impl Morris {
+    pub fn new() -> Morris {
+        Morris {
+            board: Board::new(),
+            to_move: 'X',
+        }
+    }
+
+    pub fn pieces(&self, sign: char) -> usize {
+        self.board
+            .grid
+            .iter()
+            .flatten()
+            .filter(|&&c| c == sign)
+            .count()
+    }
+
+    pub fn is_placing(&self) -> bool {
+        self.pieces(self.to_move) < PIECES
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if winning_line(self.board).is_some() {
+            return Vec::new();
+        }
+
+        let cells = (0..9).map(|i| (i % 3, i / 3)).collect::<Vec<Coordinate>>();
+        let value = |(x, y): Coordinate| self.board.grid[y][x];
+        if self.is_placing() {
+            return cells
+                .into_iter()
+                .filter(|&cell| value(cell) == '-')
+                .map(Move::Place)
+                .collect();
+        }
+
+        let mut moves = Vec::new();
+        for &from in cells.iter().filter(|&&cell| value(cell) == self.to_move) {
+            for &to in cells.iter() {
+                if value(to) == '-' && is_adjacent(from, to) {
+                    moves.push(Move::Slide(from, to));
+                }
+            }
+        }
+        moves
+    }
+
+    pub fn play(&self, chosen: Move) -> Result<Morris, String> {
+        if self.is_over() {
+            return Err("The game is already over".to_string());
+        }
+
+        let board = match (chosen, self.is_placing()) {
+            (Move::Place(cell), true) => self.board.add_value(cell, self.to_move)?,
+            (Move::Place(_), false) => {
+                return Err("All your pieces are out, slide one of them instead".to_string())
+            }
+            (Move::Slide(..), true) => {
+                return Err("Place all your pieces before sliding them".to_string())
+            }
+            (Move::Slide(from, to), false) => {
+                if from.0 > 2 || from.1 > 2 || self.board.grid[from.1][from.0] != self.to_move {
+                    return Err("You can only slide your own pieces".to_string());
+                }
+                if !is_adjacent(from, to) {
+                    return Err("Pieces only slide to an adjacent cell".to_string());
+                }
+                let mut board = self.board.add_value(to, self.to_move)?;
+                board.grid[from.1][from.0] = '-';
+                board
+            }
+        };
+
+        Ok(Morris {
+            board,
+            to_move: get_opponent_sign(self.to_move),
+        })
+    }
+
+    pub fn winner(&self) -> Option<char> {
+        match winning_line(self.board) {
+            Some([(x, y), _, _]) => Some(self.board.grid[y][x]),
+            None if self.legal_moves().is_empty() => Some(get_opponent_sign(self.to_move)),
+            None => None,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some()
+    }
+
+    fn key(&self) -> Key {
+        (self.board.grid, self.to_move)
+    }
+}
+
+impl Rules for Morris {
+    type Move = Move;
+
+    fn to_move(&self) -> char {
+        self.to_move
+    }
+
+    fn legal_moves(&self) -> Vec<Move> {
+        Morris::legal_moves(self)
+    }
+
+    fn play(&self, chosen: Move) -> Result<Morris, String> {
+        Morris::play(self, chosen)
+    }
+
+    fn status(&self) -> Status {
+        match Morris::winner(self) {
+            Some(sign) => Status::Won(sign),
+            None => Status::Ongoing,
+        }
+    }
+}
+
+impl Variant for Morris {
+    fn prompt(&self) -> String {
+        if self.is_placing() {
+            "enter a cell from 1 to 9 to place a piece on".to_string()
+        } else {
+            "enter the cells to slide from and to, like 15".to_string()
+        }
+    }
+
+    fn parse_move(&self, input: &str) -> Result<Move, String> {
+        parse_move(input, self)
+    }
+
+    fn format_move(&self, chosen: Move) -> String {
+        format_move(chosen)
+    }
+
+    fn render(&self) -> String {
+        render(self)
+    }
+
+    fn result(&self) -> String {
+        match Morris::winner(self) {
+            Some(winner) if winning_line(self.board).is_none() => {
+                format!("Player {} can't move, player {} won!", self.to_move, winner)
+            }
+            Some(winner) => format!("Player {} won!", winner),
+            None => "It's a tie!".to_string(),
+        }
+    }
+
+    fn repetitions(&self) -> Option<usize> {
+        Some(REPETITIONS)
+    }
+}
+
+// A placement is one cell like `5`, a slide two like `15` or `1 5`.
+pub fn parse_move(input: &str, position: &Morris) -> Result<Move, String> {
+    let digits = input
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | ',' | '-' | '>'))
+        .map(|c| c.to_string())
+        .collect::<Vec<String>>();
+
+    match (digits.len(), position.is_placing()) {
+        (1, true) => Ok(Move::Place(CellNumbers.parse(&digits[0])?)),
+        (2, false) => Ok(Move::Slide(
+            CellNumbers.parse(&digits[0])?,
+            CellNumbers.parse(&digits[1])?,
+        )),
+        (_, true) => Err("Enter the cell to place a piece on, from 1 to 9".to_string()),
+        (_, false) => {
+            Err("Enter the cell to slide from and the one to slide to, like 15".to_string())
+        }
+    }
+}
+
+pub fn format_move(chosen: Move) -> String {
+    match chosen {
+        Move::Place(cell) => CellNumbers.format(cell),
+        Move::Slide(from, to) => format!("{}{}", CellNumbers.format(from), CellNumbers.format(to)),
+    }
+}
+
+// The board with the lines pieces slide along.
+pub fn render(position: &Morris) -> String {
+    let row = |y: usize| {
+        position.board.grid[y]
+            .iter()
+            .map(|&c| if c == '-' { '.' } else { c }.to_string())
+            .collect::<Vec<String>>()
+            .join("---")
+    };
+    [
+        row(0),
+        "| \\ | / |".to_string(),
+        row(1),
+        "| / | \\ |".to_string(),
+        row(2),
+    ]
+    .join("\n")
+}
+
+// Pieces can slide back and forth forever, so a plain search wouldn't end.
+// Instead this works backwards from the finished games through every
+// position that can come up, and whatever can't be forced either way is a
+// draw.
+fn solve() -> HashMap<Key, Value> {
+    let mut positions = vec![Morris::new()];
+    let mut index = HashMap::new();
+    index.insert(Morris::new().key(), 0);
+    let mut children: Vec<Vec<usize>> = Vec::new();
+
+    let mut next = 0;
+    while next < positions.len() {
+        let position = positions[next];
+        let mut found = Vec::new();
+        for chosen in position.legal_moves() {
+            let child = position.play(chosen).expect("legal moves can be played");
+            let id = *index.entry(child.key()).or_insert_with(|| {
+                positions.push(child);
+                positions.len() - 1
+            });
+            found.push(id);
+        }
+        children.push(found);
+        next += 1;
+    }
+
+    let mut parents = vec![Vec::new(); positions.len()];
+    for (parent, found) in children.iter().enumerate() {
+        for &child in found {
+            parents[child].push(parent);
+        }
+    }
+
+    let mut values: Vec<Option<Value>> = vec![None; positions.len()];
+    let mut undecided = children.iter().map(Vec::len).collect::<Vec<usize>>();
+    let mut queue = VecDeque::new();
+    for (id, found) in children.iter().enumerate() {
+        if found.is_empty() {
+            values[id] = Some(Value::Loss(0));
+            queue.push_back(id);
+        }
+    }
+
+    while let Some(id) = queue.pop_front() {
+        for &parent in parents[id].iter() {
+            if values[parent].is_some() {
+                continue;
+            }
+            match values[id] {
+                Some(value @ Value::Loss(_)) => {
+                    values[parent] = Some(value.before());
+                    queue.push_back(parent);
+                }
+                Some(value @ Value::Win(_)) => {
+                    undecided[parent] -= 1;
+                    if undecided[parent] == 0 {
+                        values[parent] = Some(value.before());
+                        queue.push_back(parent);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    positions
+        .iter()
+        .zip(values)
+        .map(|(position, value)| (position.key(), value.unwrap_or(Value::Draw)))
+        .collect()
+}
+
+pub fn value(position: &Morris) -> Value {
+    static VALUES: OnceLock<HashMap<Key, Value>> = OnceLock::new();
+    VALUES
+        .get_or_init(solve)
+        .get(&position.key())
+        .cloned()
+        .unwrap_or(Value::Draw)
+}
+
+// Wins as fast as possible, holds a draw if it can't, and otherwise loses as
+// slowly as possible.
+pub fn best_move(position: &Morris) -> Option<Move> {
+    let rank = |chosen: &Move| {
+        let next = position.play(*chosen).expect("legal moves can be played");
+        value(&next).before().rank()
+    };
+    position
+        .legal_moves()
+        .into_iter()
+        .max_by_key(|chosen| rank(chosen))
+}
+
+// Plays perfectly on hard. On medium it takes a win it sees and otherwise
+// avoids handing one over, and on easy it plays at random.
+pub struct MorrisRobot {
+    sign: char,
+    difficulty: Difficulty,
+    random: Random,
+}
+
+impl MorrisRobot {
+    pub fn new(sign: char) -> MorrisRobot {
+        MorrisRobot {
+            sign,
+            difficulty: Difficulty::Hard,
+            random: Random::from_time(),
+        }
+    }
+
+    pub fn with_difficulty(self, difficulty: Difficulty) -> Self {
+        MorrisRobot { difficulty, ..self }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        MorrisRobot {
+            random: Random::new(seed),
+            ..self
+        }
+    }
+}
+
+impl VariantPlayer<Morris> for MorrisRobot {
+    fn sign(&self) -> char {
+        self.sign
+    }
+
+    fn choose_move(&mut self, position: &Morris) -> Result<Option<Move>, String> {
+        let moves = position.legal_moves();
+        let after = |chosen: &Move| position.play(*chosen).expect("legal moves can be played");
+        let chosen = match self.difficulty {
+            Difficulty::Easy => self.random.choose(&moves).cloned(),
+            Difficulty::Medium => {
+                let safe = moves
+                    .iter()
+                    .cloned()
+                    .filter(|chosen| {
+                        let next = after(chosen);
+                        next.is_over() || !next.legal_moves().iter().any(|&m| after_wins(&next, m))
+                    })
+                    .collect::<Vec<Move>>();
+                match moves.iter().find(|chosen| after(chosen).is_over()) {
+                    Some(&winning) => Some(winning),
+                    None if safe.is_empty() => self.random.choose(&moves).cloned(),
+                    None => self.random.choose(&safe).cloned(),
+                }
+            }
+            Difficulty::Hard => best_move(position),
+        };
+        chosen
+            .map(Some)
+            .ok_or_else(|| "No choice found".to_string())
+    }
+}
+
+fn after_wins(position: &Morris, chosen: Move) -> bool {
+    position
+        .play(chosen)
+        .is_ok_and(|next| next.winner() == Some(position.to_move))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::{Outcome, Transcript};
+    use std::io::{self, Cursor};
+    use variant::{play, VariantHuman};
+
+    fn position(rows: [&str; 3], to_move: char) -> Morris {
+        let mut board = Board::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                board.grid[y][x] = c;
+            }
+        }
+        Morris { board, to_move }
+    }
+
+    #[test]
+    fn pieces_slide_once_all_are_placed() {
+        let placing = position(["XO-", "-X-", "O--"], 'X');
+        assert!(placing.is_placing());
+        assert_eq!(placing.legal_moves().len(), 5);
+        assert!(placing.play(Move::Slide((0, 0), (0, 1))).is_err());
+
+        let moving = position(["XO-", "-X-", "OXO"], 'X');
+        assert!(!moving.is_placing());
+        assert!(moving.play(Move::Place((2, 0))).is_err());
+        assert!(moving.legal_moves().contains(&Move::Slide((1, 1), (2, 0))));
+    }
+
+    #[test]
+    fn slides_follow_the_lines() {
+        assert!(is_adjacent((0, 0), (1, 0)));
+        assert!(is_adjacent((0, 0), (1, 1)));
+        assert!(is_adjacent((1, 1), (2, 2)));
+        assert!(!is_adjacent((1, 0), (0, 1)));
+        assert!(!is_adjacent((0, 0), (2, 0)));
+
+        let moving = position(["XO-", "X--", "OOX"], 'X');
+        assert!(moving.play(Move::Slide((0, 1), (1, 0))).is_err());
+        assert!(moving.play(Move::Slide((1, 2), (1, 1))).is_err());
+        let next = moving.play(Move::Slide((0, 1), (1, 1))).unwrap();
+        assert_eq!(next.board.grid[1], ['-', 'X', '-']);
+        assert_eq!(next.to_move, 'O');
+    }
+
+    #[test]
+    fn sliding_into_a_line_wins() {
+        let moving = position(["X-O", "OX-", "O-X"], 'X');
+        assert_eq!(moving.winner(), Some('X'));
+
+        let moving = position(["X-O", "OXX", "O--"], 'X');
+        let next = moving.play(Move::Slide((1, 1), (1, 2))).unwrap();
+        assert_eq!(next.winner(), None);
+        let next = moving.play(Move::Slide((2, 1), (2, 2))).unwrap();
+        assert_eq!(next.winner(), Some('X'));
+    }
+
+    #[test]
+    fn third_repetition_is_a_draw() {
+        let human = |sign, moves: &'static str| {
+            VariantHuman::new(sign, Box::new(Cursor::new(moves)), Box::new(io::sink()))
+        };
+        let start = position(["XO-", "-X-", "OXO"], 'X');
+        let mut output = Transcript::new();
+
+        let outcome = play(
+            start,
+            [
+                Box::new(human('X', "14\n41\n14\n41\n")),
+                Box::new(human('O', "23\n32\n23\n32\n")),
+            ],
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::Draw);
+        assert!(output.contents().contains("came up 3 times"));
+    }
+
+    #[test]
+    fn parses_placements_and_slides() {
+        let placing = Morris::new();
+        assert_eq!(parse_move("5", &placing), Ok(Move::Place((1, 1))));
+        assert!(parse_move("15", &placing).is_err());
+
+        let moving = position(["XO-", "-X-", "OXO"], 'X');
+        assert_eq!(parse_move("5 3", &moving), Ok(Move::Slide((1, 1), (2, 0))));
+        assert_eq!(format_move(Move::Slide((1, 1), (2, 0))), "53");
+        assert!(parse_move("5", &moving).is_err());
+    }
+
+    #[test]
+    fn renders_the_lines() {
+        let expected = "X---O---.
+| \\ | / |
+.---X---.
+| / | \\ |
+O---.---.";
+        assert_eq!(render(&position(["XO-", "-X-", "O--"], 'O')), expected);
+    }
+
+    #[test]
+    fn solver_finds_forced_results() {
+        assert_eq!(value(&position(["X-O", "OXX", "O--"], 'X')), Value::Win(1));
+        assert_eq!(value(&position(["X-O", "OX-", "O-X"], 'O')), Value::Loss(0));
+        assert_eq!(
+            best_move(&position(["X-O", "OXX", "O--"], 'X')),
+            Some(Move::Slide((2, 1), (2, 2)))
+        );
+    }
+
+    #[test]
+    fn perfect_robot_never_loses() {
+        for seed in 0..10 {
+            for &first in [true, false].iter() {
+                let robot = MorrisRobot::new(if first { 'X' } else { 'O' });
+                let random = MorrisRobot::new(if first { 'O' } else { 'X' })
+                    .with_difficulty(Difficulty::Easy)
+                    .with_seed(seed);
+                let sign = robot.sign();
+                let outcome = play(
+                    Morris::new(),
+                    [Box::new(robot), Box::new(random)],
+                    &mut io::sink(),
+                )
+                .unwrap();
+                assert_ne!(outcome, Outcome::Won(get_opponent_sign(sign)));
+            }
+        }
+    }
+}
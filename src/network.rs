@@ -0,0 +1,293 @@
+use board::Board;
+use game::Game;
+use players::{Action, Player};
+use render::Render;
+use robot_player::get_opponent_sign;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use user_input::parse_user_input;
+
+pub const DEFAULT_ADDRESS: &str = "0.0.0.0:7878";
+
+// The protocol is line based. The server sends `WELCOME <sign>` once, then
+// `BOARD <cells>` followed by `TURN` whenever the client has to move,
+// `ERROR <message>` for rejected moves and `GAME_OVER <message>` at the end.
+// The client answers every `TURN` with `MOVE x,y`.
+
+pub struct RemotePlayer {
+    sign: char,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl RemotePlayer {
+    pub fn new(sign: char, stream: TcpStream) -> io::Result<RemotePlayer> {
+        let reader = BufReader::new(stream.try_clone()?);
+        let mut player = RemotePlayer {
+            sign,
+            reader,
+            writer: stream,
+        };
+        player.send(&format!("WELCOME {}", sign))?;
+        Ok(player)
+    }
+
+    fn send(&mut self, message: &str) -> io::Result<()> {
+        writeln!(self.writer, "{}", message)
+    }
+
+    fn receive(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(line.trim().to_string()))
+        }
+    }
+
+    fn request_move(&mut self, board: Board) -> io::Result<Option<String>> {
+        self.send(&format!("BOARD {}", board.to_cells()))?;
+        self.send("TURN")?;
+        self.receive()
+    }
+}
+
+impl Player for RemotePlayer {
+    fn sign(&self) -> char {
+        self.sign
+    }
+
+    fn choose_action(&mut self, board: Board) -> Result<Action, String> {
+        let line = match self.request_move(board) {
+            Ok(Some(line)) => line,
+            // The game reports that the player left.
+            Ok(None) | Err(_) => return Ok(Action::Abandon),
+        };
+
+        let result = match split_message(&line) {
+            ("MOVE", coordinate) => parse_user_input(coordinate)
+                .and_then(|coordinate| board.add_value(coordinate, self.sign).map(|_| coordinate)),
+            _ => Err(format!("Unexpected message: {}", line)),
+        };
+
+        match result {
+            Ok(coordinate) => Ok(Action::Place(coordinate)),
+            Err(e) => {
+                let _ = self.send(&format!("ERROR {}", e));
+                Err(e)
+            }
+        }
+    }
+
+    fn game_over(&mut self, board: Board, message: &str) {
+        let _ = self.send(&format!("BOARD {}", board.to_cells()));
+        let _ = self.send(&format!("GAME_OVER {}", message));
+    }
+}
+
+pub fn serve(
+    listener: &TcpListener,
+    host: Box<dyn Player>,
+    renderer: Box<dyn Render>,
+) -> io::Result<Game> {
+    println!("Waiting for an opponent on {}", listener.local_addr()?);
+    let (stream, peer) = listener.accept()?;
+    println!("Player connected from {}", peer);
+
+    let remote = RemotePlayer::new(get_opponent_sign(host.sign()), stream)?;
+    Ok(Game::new([host, Box::new(remote)])
+        .with_renderer(renderer)
+        .play())
+}
+
+pub fn connect<F>(address: &str, renderer: &dyn Render, create_player: F) -> io::Result<String>
+where
+    F: FnOnce(char) -> Box<dyn Player>,
+{
+    let stream = TcpStream::connect(address)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    let line = read_message(&mut reader)?;
+    let sign = match split_message(&line) {
+        ("WELCOME", sign) => sign.chars().next().ok_or_else(|| protocol_error(&line))?,
+        _ => return Err(protocol_error(&line)),
+    };
+    println!("You are player '{}'", sign);
+
+    let mut player = create_player(sign);
+    let mut board = Board::new();
+
+    loop {
+        let line = read_message(&mut reader)?;
+        match split_message(&line) {
+            ("BOARD", cells) => {
+                board = Board::from_cells(cells).map_err(|e| protocol_error(&e))?;
+                println!("{}", renderer.render(board, None));
+            }
+            ("TURN", _) => loop {
+                match player.choose_action(board) {
+                    Ok(Action::Place(coordinate)) => {
+                        writeln!(writer, "MOVE {},{}", coordinate.0, coordinate.1)?;
+                        break;
+                    }
+                    Ok(Action::Abandon) | Ok(Action::Resign) => {
+                        return Ok("You left the game".to_string())
+                    }
+                    Ok(Action::ShowBoard) => println!("{}", renderer.render(board, None)),
+                    Ok(_) => println!("That isn't available in network games"),
+                    Err(e) => println!("{}", e),
+                }
+            },
+            ("ERROR", message) => println!("{}", message),
+            ("GAME_OVER", message) => {
+                println!("Game over");
+                println!("{}", message);
+                player.game_over(board, message);
+                return Ok(message.to_string());
+            }
+            _ => return Err(protocol_error(&line)),
+        }
+    }
+}
+
+fn read_message(reader: &mut BufReader<TcpStream>) -> io::Result<String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "The server closed the connection",
+        ));
+    }
+    Ok(line.trim().to_string())
+}
+
+fn split_message(line: &str) -> (&str, &str) {
+    match line.find(' ') {
+        Some(i) => (&line[..i], line[i + 1..].trim()),
+        None => (line, ""),
+    }
+}
+
+fn protocol_error(line: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Unexpected message from the server: {}", line),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use render::TextRenderer;
+    use robot_player::RobotPlayer;
+    use std::thread;
+
+    fn plain() -> Box<dyn Render> {
+        Box::new(TextRenderer::plain())
+    }
+
+    fn listen() -> (TcpListener, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        (listener, address)
+    }
+
+    struct ScriptedPlayer {
+        sign: char,
+        moves: Vec<&'static str>,
+    }
+
+    impl Player for ScriptedPlayer {
+        fn sign(&self) -> char {
+            self.sign
+        }
+
+        fn choose_action(&mut self, _board: Board) -> Result<Action, String> {
+            if self.moves.is_empty() {
+                return Ok(Action::Abandon);
+            }
+            parse_user_input(self.moves.remove(0)).map(Action::Place)
+        }
+    }
+
+    #[test]
+    fn board_survives_round_trip_through_cells() {
+        let grid = [['O', '-', 'X'], ['-', 'X', '-'], ['O', '-', '-']];
+        let board = Board::from_cells(&Board { grid }.to_cells()).unwrap();
+        assert_eq!(board.grid, grid);
+    }
+
+    #[test]
+    fn robots_on_both_ends_tie() {
+        let (listener, address) = listen();
+        let client = thread::spawn(move || {
+            connect(&address, &TextRenderer::plain(), |sign| {
+                Box::new(RobotPlayer::new(sign)) as Box<dyn Player>
+            })
+        });
+
+        let game = serve(&listener, Box::new(RobotPlayer::new('O')), plain()).unwrap();
+
+        assert_eq!(game.get_final_message(), "It's a tie!");
+        assert_eq!(client.join().unwrap().unwrap(), "It's a tie!");
+    }
+
+    #[test]
+    fn invalid_remote_moves_are_rejected() {
+        let (listener, address) = listen();
+        let client = thread::spawn(move || {
+            connect(&address, &TextRenderer::plain(), |sign| {
+                Box::new(ScriptedPlayer {
+                    sign,
+                    moves: vec!["1,1", "0,1", "0,2", "1,0"],
+                }) as Box<dyn Player>
+            })
+        });
+
+        let host = ScriptedPlayer {
+            sign: 'O',
+            moves: vec!["1,1", "2,0", "2,1", "2,2"],
+        };
+        let game = serve(&listener, Box::new(host), plain()).unwrap();
+
+        assert_eq!(game.get_final_message(), "Player O won!");
+        assert_eq!(client.join().unwrap().unwrap(), "Player O won!");
+    }
+
+    #[test]
+    fn welcome_without_a_sign_is_an_error() {
+        let (listener, address) = listen();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            writeln!(stream, "WELCOME").unwrap();
+        });
+
+        let result = connect(&address, &TextRenderer::plain(), |sign| {
+            Box::new(RobotPlayer::new(sign)) as Box<dyn Player>
+        });
+        server.join().unwrap();
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn disconnect_abandons_the_game() {
+        let (listener, address) = listen();
+        let client = thread::spawn(move || {
+            let stream = TcpStream::connect(&address).unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+        });
+
+        let host = ScriptedPlayer {
+            sign: 'O',
+            moves: vec!["1,1"],
+        };
+        let game = serve(&listener, Box::new(host), plain()).unwrap();
+        client.join().unwrap();
+
+        assert_eq!(game.get_final_message(), "Player X left the game");
+    }
+}
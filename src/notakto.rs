@@ -0,0 +1,641 @@
+use board::Board;
+use notation::{CellNumbers, Notation};
+use players::Coordinate;
+use random::Random;
+use robot_player::Difficulty;
+use rules::{Rules, Status};
+use variant::{Variant, VariantPlayer};
+use win_condition::winning_line;
+
+// Notakto is played on several boards at once, and both players place X. A
+// board with three in a row is dead and can't be played on any more, and
+// whoever kills the last board loses.
+//
+// Boards are numbered from 1 and cells from 1 to 9 in reading order, so `25`
+// is the center of the second board.
+//
+// The robot uses the misère quotient found by Plambeck and Whitehead in
+// "The Secrets of Notakto". Every board is worth an element of a monoid with
+// 18 elements, the boards of a position multiply together, and the position
+// is lost for the player to move exactly when the product is one of `a`,
+// `b²`, `bc` or `c²`.
+
+pub const MARK: char = 'X';
+
+// The board and the cell within it.
+pub type Move = (usize, Coordinate);
+
+// An element of the quotient as the exponents of its generators a, b, c and
+// d, kept in the normal form given by the relations
+// a² = 1, b³ = b, b²c = c, c³ = ac², b²d = d, cd = ad and d² = c².
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quotient {
+    a: u8,
+    b: u8,
+    c: u8,
+    d: u8,
+}
+
+impl Quotient {
+    pub const ONE: Quotient = Quotient {
+        a: 0,
+        b: 0,
+        c: 0,
+        d: 0,
+    };
+
+    // Reads a word like `ab` or `cc`, `1` being the identity.
+    pub fn parse(word: &str) -> Option<Quotient> {
+        let mut value = Quotient::ONE;
+        for generator in word.chars() {
+            match generator {
+                'a' => value.a += 1,
+                'b' => value.b += 1,
+                'c' => value.c += 1,
+                'd' => value.d += 1,
+                '1' if word.len() == 1 => {}
+                _ => return None,
+            }
+        }
+        Some(value.normalized())
+    }
+
+    pub fn multiply(self, other: Quotient) -> Quotient {
+        Quotient {
+            a: self.a + other.a,
+            b: self.b + other.b,
+            c: self.c + other.c,
+            d: self.d + other.d,
+        }
+        .normalized()
+    }
+
+    // Positions worth one of these are lost for the player to move.
+    pub fn is_p_position(self) -> bool {
+        ["a", "bb", "bc", "cc"]
+            .iter()
+            .any(|word| Quotient::parse(word) == Some(self))
+    }
+
+    fn normalized(mut self) -> Quotient {
+        loop {
+            if self.a >= 2 {
+                self.a -= 2;
+            } else if self.b >= 3 || (self.b >= 2 && (self.c >= 1 || self.d >= 1)) {
+                self.b -= 2;
+            } else if self.c >= 3 || (self.c >= 1 && self.d >= 1) {
+                self.c -= 1;
+                self.a += 1;
+            } else if self.d >= 2 {
+                self.d -= 2;
+                self.c += 2;
+            } else {
+                return self;
+            }
+        }
+    }
+}
+
+// The worth of every live board up to rotation and reflection, with the
+// cells in reading order and the rows separated by `/`.
+const BOARD_VALUES: [(&str, &str); 46] = [
+    ("---/---/---", "c"),
+    ("X--/---/---", "1"),
+    ("-X-/---/---", "1"),
+    ("XX-/---/---", "d"),
+    ("X-X/---/---", "b"),
+    ("-X-/X--/---", "a"),
+    ("XX-/X--/---", "b"),
+    ("--X/X--/---", "b"),
+    ("X-X/X--/---", "a"),
+    ("-XX/X--/---", "ad"),
+    ("---/-X-/---", "cc"),
+    ("X--/-X-/---", "b"),
+    ("-X-/-X-/---", "b"),
+    ("XX-/-X-/---", "ab"),
+    ("X-X/-X-/---", "a"),
+    ("-X-/XX-/---", "ab"),
+    ("XX-/XX-/---", "a"),
+    ("--X/XX-/---", "a"),
+    ("X-X/XX-/---", "b"),
+    ("-XX/XX-/---", "b"),
+    ("---/X-X/---", "a"),
+    ("X--/X-X/---", "ad"),
+    ("-X-/X-X/---", "b"),
+    ("XX-/X-X/---", "a"),
+    ("X-X/X-X/---", "b"),
+    ("--X/---/X--", "a"),
+    ("X-X/---/X--", "ab"),
+    ("-XX/---/X--", "ad"),
+    ("-XX/X--/X--", "ab"),
+    ("X--/--X/X--", "a"),
+    ("-X-/--X/X--", "1"),
+    ("XX-/--X/X--", "b"),
+    ("X-X/--X/X--", "b"),
+    ("-XX/--X/X--", "a"),
+    ("-X-/X-X/X--", "ab"),
+    ("--X/X-X/X--", "a"),
+    ("-XX/X-X/X--", "b"),
+    ("X--/-XX/X--", "b"),
+    ("-X-/-XX/X--", "b"),
+    ("XX-/-XX/X--", "a"),
+    ("-X-/X-X/-X-", "a"),
+    ("XX-/X-X/-X-", "b"),
+    ("X-X/X-X/-X-", "a"),
+    ("X-X/--X/XX-", "a"),
+    ("-XX/X-X/XX-", "a"),
+    ("X-X/---/X-X", "a"),
+];
+
+// One bit per marked cell, in reading order.
+fn mask(cells: impl Iterator<Item = bool>) -> u16 {
+    cells
+        .enumerate()
+        .filter(|&(_, marked)| marked)
+        .fold(0, |mask, (i, _)| mask | 1 << i)
+}
+
+// The smallest mask among the rotations and reflections of the board.
+fn canonical_mask(board: Board) -> u16 {
+    (0..8)
+        .map(|symmetry| {
+            mask((0..9).map(|i| {
+                let (mut x, mut y) = (i % 3, i / 3);
+                for _ in 0..symmetry / 2 {
+                    let rotated = (2 - y, x);
+                    x = rotated.0;
+                    y = rotated.1;
+                }
+                if symmetry % 2 == 1 {
+                    x = 2 - x;
+                }
+                board.grid[y][x] != '-'
+            }))
+        })
+        .min()
+        .expect("there are eight symmetries")
+}
+
+// Dead boards are worth `1` as they can't change the outcome any more.
+pub fn board_value(board: Board) -> Quotient {
+    if winning_line(board).is_some() {
+        return Quotient::ONE;
+    }
+    let wanted = canonical_mask(board);
+    BOARD_VALUES
+        .iter()
+        .find(|(grid, _)| mask(grid.chars().filter(|&c| c != '/').map(|c| c == MARK)) == wanted)
+        .and_then(|(_, word)| Quotient::parse(word))
+        .expect("every live board is in the table")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notakto {
+    pub boards: Vec<Board>,
+    // Which player is to move, not the mark they place.
+    pub to_move: char,
+}
+
+impl Notakto {
+    pub fn new(boards: usize) -> Notakto {
+        Notakto {
+            boards: vec![Board::new(); boards],
+            to_move: '1',
+        }
+    }
+
+    pub fn is_dead(&self, board: usize) -> bool {
+        winning_line(self.boards[board]).is_some()
+    }
+
+    pub fn live_boards(&self) -> Vec<usize> {
+        (0..self.boards.len())
+            .filter(|&board| !self.is_dead(board))
+            .collect()
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for board in self.live_boards() {
+            for cell in (0..9).map(|i| (i % 3, i / 3)) {
+                if self.boards[board].grid[cell.1][cell.0] == '-' {
+                    moves.push((board, cell));
+                }
+            }
+        }
+        moves
+    }
+
+    pub fn play(&self, (board, cell): Move) -> Result<Notakto, String> {
+        if board >= self.boards.len() {
+            return Err(format!("There are only {} boards", self.boards.len()));
+        }
+        if self.is_dead(board) {
+            return Err("That board is already dead".to_string());
+        }
+
+        let mut next = self.clone();
+        next.boards[board] = self.boards[board].add_value(cell, MARK)?;
+        next.to_move = other_player(self.to_move);
+        Ok(next)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.live_boards().is_empty()
+    }
+
+    // Whoever killed the last board lost, so it's the player to move.
+    pub fn winner(&self) -> Option<char> {
+        if self.is_over() {
+            Some(self.to_move)
+        } else {
+            None
+        }
+    }
+
+    pub fn value(&self) -> Quotient {
+        self.boards.iter().fold(Quotient::ONE, |value, &board| {
+            value.multiply(board_value(board))
+        })
+    }
+}
+
+impl Rules for Notakto {
+    type Move = Move;
+
+    fn to_move(&self) -> char {
+        self.to_move
+    }
+
+    fn legal_moves(&self) -> Vec<Move> {
+        Notakto::legal_moves(self)
+    }
+
+    fn play(&self, chosen: Move) -> Result<Notakto, String> {
+        Notakto::play(self, chosen)
+    }
+
+    fn status(&self) -> Status {
+        match Notakto::winner(self) {
+            Some(player) => Status::Won(player),
+            None => Status::Ongoing,
+        }
+    }
+}
+
+impl Variant for Notakto {
+    fn prompt(&self) -> String {
+        "enter a board and a cell, like 25".to_string()
+    }
+
+    fn parse_move(&self, input: &str) -> Result<Move, String> {
+        parse_move(input, self)
+    }
+
+    fn format_move(&self, chosen: Move) -> String {
+        format_move(chosen)
+    }
+
+    fn render(&self) -> String {
+        render(self)
+    }
+
+    fn result(&self) -> String {
+        format!(
+            "Player {} killed the last board, player {} won!",
+            other_player(self.to_move),
+            self.to_move
+        )
+    }
+}
+
+pub fn other_player(player: char) -> char {
+    if player == '1' {
+        '2'
+    } else {
+        '1'
+    }
+}
+
+// Reads `<board><cell>` like `25`, or `2 5` when there are more than nine
+// boards. The board can be left out when only one is still alive.
+pub fn parse_move(input: &str, position: &Notakto) -> Result<Move, String> {
+    let parts = input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>();
+    let (board, cell) = match parts.as_slice() {
+        [board, cell] => (board.to_string(), cell.to_string()),
+        [both] => match both.chars().collect::<Vec<char>>().as_slice() {
+            [board, cell] if board.is_ascii_digit() && cell.is_ascii_digit() => {
+                (board.to_string(), cell.to_string())
+            }
+            [cell] if cell.is_ascii_digit() => match position.live_boards().as_slice() {
+                [only] => ((only + 1).to_string(), cell.to_string()),
+                _ => return Err("Enter the board and the cell, like 25".to_string()),
+            },
+            _ => return Err("Enter the board and the cell, like 25".to_string()),
+        },
+        _ => return Err("Enter the board and the cell, like 25".to_string()),
+    };
+
+    let board = board
+        .parse::<usize>()
+        .ok()
+        .filter(|&board| board >= 1 && board <= position.boards.len())
+        .ok_or_else(|| format!("The board has to be from 1 to {}", position.boards.len()))?;
+    Ok((board - 1, CellNumbers.parse(&cell)?))
+}
+
+pub fn format_move((board, cell): Move) -> String {
+    format!("{}{}", board + 1, CellNumbers.format(cell))
+}
+
+// The boards side by side, each headed by its number.
+pub fn render(position: &Notakto) -> String {
+    let columns = (0..position.boards.len())
+        .map(|board| {
+            let label = if position.is_dead(board) {
+                format!("{} (dead)", board + 1)
+            } else {
+                (board + 1).to_string()
+            };
+            let mut column = vec![label];
+            for row in position.boards[board].grid.iter() {
+                let cells = row.iter().map(|c| c.to_string()).collect::<Vec<String>>();
+                column.push(cells.join(" "));
+            }
+            column
+        })
+        .collect::<Vec<Vec<String>>>();
+
+    (0..4)
+        .map(|line| {
+            columns
+                .iter()
+                .map(|column| format!("{:<11}", column[line]))
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Moves to a lost position for the opponent whenever there is one. On medium
+// it only steers clear of killing the last board, and on easy it plays at
+// random.
+pub struct NotaktoRobot {
+    player: char,
+    difficulty: Difficulty,
+    random: Random,
+}
+
+impl NotaktoRobot {
+    pub fn new(player: char) -> NotaktoRobot {
+        NotaktoRobot {
+            player,
+            difficulty: Difficulty::Hard,
+            random: Random::from_time(),
+        }
+    }
+
+    pub fn with_difficulty(self, difficulty: Difficulty) -> Self {
+        NotaktoRobot { difficulty, ..self }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        NotaktoRobot {
+            random: Random::new(seed),
+            ..self
+        }
+    }
+}
+
+impl VariantPlayer<Notakto> for NotaktoRobot {
+    fn sign(&self) -> char {
+        self.player
+    }
+
+    fn choose_move(&mut self, position: &Notakto) -> Result<Option<Move>, String> {
+        let moves = position.legal_moves();
+        let chosen = match self.difficulty {
+            Difficulty::Easy => self.random.choose(&moves).cloned(),
+            Difficulty::Medium => {
+                let safe = moves
+                    .iter()
+                    .cloned()
+                    .filter(|&chosen| !position.play(chosen).is_ok_and(|next| next.is_over()))
+                    .collect::<Vec<Move>>();
+                if safe.is_empty() {
+                    self.random.choose(&moves).cloned()
+                } else {
+                    self.random.choose(&safe).cloned()
+                }
+            }
+            Difficulty::Hard => best_move(position).or_else(|| self.random.choose(&moves).cloned()),
+        };
+        chosen
+            .map(Some)
+            .ok_or_else(|| "No choice found".to_string())
+    }
+}
+
+// A move that leaves the opponent in a lost position, if there is one. When
+// there isn't, a move that keeps the game going is the next best thing.
+pub fn best_move(position: &Notakto) -> Option<Move> {
+    let moves = position.legal_moves();
+    let after = |chosen: &Move| position.play(*chosen).expect("legal moves can be played");
+
+    moves
+        .iter()
+        .find(|chosen| after(chosen).value().is_p_position())
+        .or_else(|| moves.iter().find(|chosen| !after(chosen).is_over()))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::Outcome;
+    use std::collections::HashMap;
+    use std::io;
+    use variant::play;
+
+    // Whether the player to move wins, found by searching the whole game.
+    fn wins_to_move(position: &Notakto, cache: &mut HashMap<Vec<u16>, bool>) -> bool {
+        let mut key = position
+            .live_boards()
+            .iter()
+            .map(|&board| canonical_mask(position.boards[board]))
+            .collect::<Vec<u16>>();
+        key.sort();
+        if key.is_empty() {
+            return true;
+        }
+        if let Some(&wins) = cache.get(&key) {
+            return wins;
+        }
+
+        let wins = position
+            .legal_moves()
+            .into_iter()
+            .any(|chosen| !wins_to_move(&position.play(chosen).unwrap(), cache));
+        cache.insert(key, wins);
+        wins
+    }
+
+    fn board(rows: &str) -> Board {
+        let mut board = Board::new();
+        for (i, c) in rows.chars().filter(|&c| c != '/').enumerate() {
+            board.grid[i / 3][i % 3] = c;
+        }
+        board
+    }
+
+    #[test]
+    fn relations_hold() {
+        let word = |w: &str| Quotient::parse(w).unwrap();
+        assert_eq!(word("dd"), word("cc"));
+        assert_eq!(word("cd"), word("ad"));
+        assert_eq!(word("ccc"), word("acc"));
+        assert_eq!(word("bbb"), word("b"));
+        assert_eq!(word("cccc"), word("cc"));
+        assert_eq!(word("aa"), Quotient::ONE);
+        assert_eq!(Quotient::parse("x"), None);
+    }
+
+    #[test]
+    fn rotations_and_reflections_share_a_value() {
+        assert_eq!(canonical_mask(board("--X/---/---")), 1);
+        assert_eq!(canonical_mask(board("---/---/X--")), 1);
+        assert_eq!(canonical_mask(board("---/X--/---")), 2);
+        assert_eq!(
+            board_value(board("---/---/---")),
+            Quotient::parse("c").unwrap()
+        );
+        assert_eq!(
+            board_value(board("---/-X-/---")),
+            Quotient::parse("cc").unwrap()
+        );
+        assert_eq!(board_value(board("XXX/---/---")), Quotient::ONE);
+    }
+
+    #[test]
+    fn quotient_agrees_with_search_for_two_boards() {
+        let mut cache = HashMap::new();
+        let live = BOARD_VALUES
+            .iter()
+            .map(|(grid, _)| board(grid))
+            .collect::<Vec<Board>>();
+        for (i, &first) in live.iter().enumerate() {
+            for &second in live[i..].iter() {
+                let position = Notakto {
+                    boards: vec![first, second],
+                    to_move: '1',
+                };
+                assert_eq!(
+                    wins_to_move(&position, &mut cache),
+                    !position.value().is_p_position(),
+                    "{:?}",
+                    position.boards
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn robot_opens_in_the_center_of_a_single_board() {
+        assert_eq!(best_move(&Notakto::new(1)), Some((0, (1, 1))));
+        assert!(Notakto::new(2).value().is_p_position());
+        assert!(!Notakto::new(3).value().is_p_position());
+    }
+
+    #[test]
+    fn medium_robot_plays_safe_moves_that_are_not_the_best() {
+        let start = Notakto::new(1);
+        let openings = (0..10)
+            .map(|seed| {
+                NotaktoRobot::new('1')
+                    .with_difficulty(Difficulty::Medium)
+                    .with_seed(seed)
+                    .choose_move(&start)
+                    .unwrap()
+            })
+            .collect::<Vec<Option<Move>>>();
+        assert!(openings.iter().any(|&chosen| chosen != best_move(&start)));
+
+        let mut position = Notakto::new(1);
+        position.boards[0] = board("XX-/---/X--");
+        for seed in 0..10 {
+            let chosen = NotaktoRobot::new('1')
+                .with_difficulty(Difficulty::Medium)
+                .with_seed(seed)
+                .choose_move(&position)
+                .unwrap()
+                .unwrap();
+            assert!(!position.play(chosen).unwrap().is_over(), "{:?}", chosen);
+        }
+    }
+
+    #[test]
+    fn killing_the_last_board_loses() {
+        let mut position = Notakto::new(2);
+        position.boards[0] = board("XXX/---/---");
+        position.boards[1] = board("XX-/---/---");
+        assert_eq!(position.winner(), None);
+        assert!(position.play((0, (1, 1))).is_err());
+
+        let position = position.play((1, (2, 0))).unwrap();
+        assert!(position.is_over());
+        assert_eq!(position.winner(), Some('2'));
+    }
+
+    #[test]
+    fn parses_board_and_cell() {
+        let position = Notakto::new(3);
+        assert_eq!(parse_move("25", &position), Ok((1, (1, 1))));
+        assert_eq!(parse_move("3 1", &position), Ok((2, (0, 0))));
+        assert_eq!(format_move((2, (0, 0))), "31");
+        assert!(parse_move("45", &position).is_err());
+        assert!(parse_move("5", &position).is_err());
+        assert_eq!(parse_move("5", &Notakto::new(1)), Ok((0, (1, 1))));
+    }
+
+    #[test]
+    fn rejects_characters_that_are_not_digits() {
+        let position = Notakto::new(3);
+        assert!(parse_move("é", &position).is_err());
+        assert!(parse_move("2é", &position).is_err());
+        assert!(parse_move("é5", &Notakto::new(1)).is_err());
+        assert!(parse_move("²", &Notakto::new(1)).is_err());
+    }
+
+    #[test]
+    fn renders_boards_side_by_side() {
+        let mut position = Notakto::new(2);
+        position.boards[0] = board("XXX/---/---");
+        position.boards[1] = board("---/-X-/---");
+        let expected = "1 (dead)   2
+X X X      - - -
+- - -      - X -
+- - -      - - -";
+        assert_eq!(render(&position), expected);
+    }
+
+    #[test]
+    fn robot_wins_from_a_winning_position() {
+        for seed in 0..10 {
+            let players: [Box<dyn VariantPlayer<Notakto>>; 2] = [
+                Box::new(NotaktoRobot::new('1')),
+                Box::new(
+                    NotaktoRobot::new('2')
+                        .with_difficulty(Difficulty::Easy)
+                        .with_seed(seed),
+                ),
+            ];
+            let outcome = play(Notakto::new(3), players, &mut io::sink()).unwrap();
+            assert_eq!(outcome, Outcome::Won('1'));
+        }
+    }
+}
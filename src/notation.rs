@@ -0,0 +1,203 @@
+use players::Coordinate;
+use user_input::parse_user_input;
+
+pub trait Notation {
+    fn parse(&self, input: &str) -> Result<Coordinate, String>;
+
+    fn format(&self, coordinate: Coordinate) -> String;
+
+    fn prompt(&self) -> &'static str;
+}
+
+pub const NAMES: [&str; 5] = ["auto", "coordinates", "algebraic", "numpad", "cells"];
+
+pub fn find_notation(name: &str) -> Option<Box<dyn Notation>> {
+    match name {
+        "auto" => Some(Box::new(AutoDetect)),
+        "coordinates" => Some(Box::new(Coordinates)),
+        "algebraic" => Some(Box::new(Algebraic)),
+        "numpad" => Some(Box::new(Numpad)),
+        "cells" => Some(Box::new(CellNumbers)),
+        _ => None,
+    }
+}
+
+// `x,y`, `x y` or `(x,y)` with both values counted from the top left.
+pub struct Coordinates;
+
+impl Notation for Coordinates {
+    fn parse(&self, input: &str) -> Result<Coordinate, String> {
+        parse_user_input(input)
+    }
+
+    fn format(&self, coordinate: Coordinate) -> String {
+        format!("{},{}", coordinate.0, coordinate.1)
+    }
+
+    fn prompt(&self) -> &'static str {
+        "Enter a coordinate in the format x,y:"
+    }
+}
+
+// Columns `a` to `c` from the left and rows `1` to `3` from the bottom, like
+// a chess board, so `a1` is the bottom left corner.
+pub struct Algebraic;
+
+impl Notation for Algebraic {
+    fn parse(&self, input: &str) -> Result<Coordinate, String> {
+        let chars = input.trim().to_lowercase().chars().collect::<Vec<char>>();
+        if chars.len() != 2 {
+            return Err("A move is a column and a row, like b2".to_string());
+        }
+
+        let x = match chars[0] {
+            'a' => 0,
+            'b' => 1,
+            'c' => 2,
+            _ => return Err("The column has to be a, b or c".to_string()),
+        };
+        let y = match chars[1] {
+            '1' => 2,
+            '2' => 1,
+            '3' => 0,
+            _ => return Err("The row has to be 1, 2 or 3".to_string()),
+        };
+        Ok((x, y))
+    }
+
+    fn format(&self, coordinate: Coordinate) -> String {
+        format!(
+            "{}{}",
+            (b'a' + coordinate.0 as u8) as char,
+            3 - coordinate.1
+        )
+    }
+
+    fn prompt(&self) -> &'static str {
+        "Enter a move like b2 (columns a-c, rows 1-3 from the bottom):"
+    }
+}
+
+// The layout of a numeric keypad, so 7 is the top left and 3 the bottom right.
+pub struct Numpad;
+
+impl Notation for Numpad {
+    fn parse(&self, input: &str) -> Result<Coordinate, String> {
+        parse_digit(input).map(|i| (i % 3, 2 - i / 3))
+    }
+
+    fn format(&self, coordinate: Coordinate) -> String {
+        ((2 - coordinate.1) * 3 + coordinate.0 + 1).to_string()
+    }
+
+    fn prompt(&self) -> &'static str {
+        "Enter a number from 1 to 9 as laid out on a numpad (7 is the top left):"
+    }
+}
+
+// The layout of a phone keypad, so 1 is the top left and 9 the bottom right.
+pub struct CellNumbers;
+
+impl Notation for CellNumbers {
+    fn parse(&self, input: &str) -> Result<Coordinate, String> {
+        parse_digit(input).map(|i| (i % 3, i / 3))
+    }
+
+    fn format(&self, coordinate: Coordinate) -> String {
+        (coordinate.1 * 3 + coordinate.0 + 1).to_string()
+    }
+
+    fn prompt(&self) -> &'static str {
+        "Enter a cell number from 1 to 9 (1 is the top left):"
+    }
+}
+
+// Accepts all of the above, reading single digits as cell numbers. Moves are
+// echoed as coordinates.
+pub struct AutoDetect;
+
+impl Notation for AutoDetect {
+    fn parse(&self, input: &str) -> Result<Coordinate, String> {
+        let trimmed = input.trim();
+        let starts_with_letter = trimmed.chars().next().is_some_and(char::is_alphabetic);
+
+        if starts_with_letter {
+            Algebraic.parse(trimmed)
+        } else if trimmed.len() == 1 {
+            CellNumbers.parse(trimmed)
+        } else {
+            Coordinates.parse(trimmed)
+        }
+    }
+
+    fn format(&self, coordinate: Coordinate) -> String {
+        Coordinates.format(coordinate)
+    }
+
+    fn prompt(&self) -> &'static str {
+        "Enter a move as x,y, b2 or a cell number from 1 to 9:"
+    }
+}
+
+fn parse_digit(input: &str) -> Result<usize, String> {
+    match input.trim().parse::<usize>() {
+        Ok(n) if (1..=9).contains(&n) => Ok(n - 1),
+        _ => Err("Enter a single number from 1 to 9".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn algebraic_counts_rows_from_bottom() {
+        assert_eq!(Algebraic.parse("a1"), Ok((0, 2)));
+        assert_eq!(Algebraic.parse(" C3 "), Ok((2, 0)));
+        assert_eq!(Algebraic.format((1, 1)), "b2");
+        assert!(Algebraic.parse("d1").is_err());
+        assert!(Algebraic.parse("a4").is_err());
+    }
+
+    #[test]
+    fn numpad_puts_seven_top_left() {
+        assert_eq!(Numpad.parse("7"), Ok((0, 0)));
+        assert_eq!(Numpad.parse("3"), Ok((2, 2)));
+        assert_eq!(Numpad.format((1, 0)), "8");
+        assert!(Numpad.parse("0").is_err());
+    }
+
+    #[test]
+    fn cell_numbers_put_one_top_left() {
+        assert_eq!(CellNumbers.parse("1"), Ok((0, 0)));
+        assert_eq!(CellNumbers.parse("6"), Ok((2, 1)));
+        assert_eq!(CellNumbers.format((2, 2)), "9");
+    }
+
+    #[test]
+    fn formats_round_trip() {
+        for name in NAMES.iter() {
+            let notation = find_notation(name).unwrap();
+            for y in 0..3 {
+                for x in 0..3 {
+                    let text = notation.format((x, y));
+                    assert_eq!(notation.parse(&text), Ok((x, y)), "{} {}", name, text);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn auto_detects_notation() {
+        assert_eq!(AutoDetect.parse("b3"), Ok((1, 0)));
+        assert_eq!(AutoDetect.parse("5"), Ok((1, 1)));
+        assert_eq!(AutoDetect.parse("2 1"), Ok((2, 1)));
+        assert_eq!(AutoDetect.parse("(0,2)"), Ok((0, 2)));
+        assert!(AutoDetect.parse("").is_err());
+    }
+
+    #[test]
+    fn unknown_notation_is_none() {
+        assert!(find_notation("roman").is_none());
+    }
+}
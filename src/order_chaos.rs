@@ -0,0 +1,444 @@
+use board::Grid;
+use players::Coordinate;
+use random::Random;
+use robot_player::Difficulty;
+use rules::{Rules, Status};
+use std::sync::OnceLock;
+use variant::{Variant, VariantPlayer};
+use win_condition::{completed_line, grid_lines};
+
+// Order and Chaos is played on a 6×6 board where both players may place X
+// or O. Order wins with five of the same mark in a row, and Chaos wins when
+// the board fills up without that happening. Order moves first.
+//
+// Moves are the mark followed by the square counted from the top left, like
+// `X 2,3`.
+
+pub const SIZE: usize = 6;
+pub const RUN: usize = 5;
+
+pub const MARKS: [char; 2] = ['X', 'O'];
+
+// The square and the mark placed on it.
+pub type Move = (Coordinate, char);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    Order,
+    Chaos,
+}
+
+pub const ROLES: [Role; 2] = [Role::Order, Role::Chaos];
+
+impl Role {
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Order => "order",
+            Role::Chaos => "chaos",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Role> {
+        ROLES.iter().cloned().find(|role| role.name() == name)
+    }
+
+    // Stands in for the player in outcomes and statistics.
+    pub fn sign(self) -> char {
+        match self {
+            Role::Order => 'R',
+            Role::Chaos => 'C',
+        }
+    }
+
+    pub fn from_sign(sign: char) -> Option<Role> {
+        ROLES.iter().cloned().find(|role| role.sign() == sign)
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Role::Order => "Order",
+            Role::Chaos => "Chaos",
+        }
+    }
+
+    pub fn other(self) -> Role {
+        match self {
+            Role::Order => Role::Chaos,
+            Role::Chaos => Role::Order,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderChaos {
+    pub grid: Grid,
+    pub to_move: Role,
+}
+
+impl Default for OrderChaos {
+    fn default() -> OrderChaos {
+        OrderChaos::new()
+    }
+}
+
+impl OrderChaos {
+    pub fn new() -> OrderChaos {
+        OrderChaos {
+            grid: Grid::new(SIZE),
+            to_move: Role::Order,
+        }
+    }
+
+    pub fn play(&self, (coordinate, mark): Move) -> Result<OrderChaos, String> {
+        if self.is_over() {
+            return Err("The game is already over".to_string());
+        }
+        if !MARKS.contains(&mark) {
+            return Err("The mark has to be X or O".to_string());
+        }
+
+        Ok(OrderChaos {
+            grid: self.grid.add_value(coordinate, mark)?,
+            to_move: self.to_move.other(),
+        })
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.is_over() {
+            return Vec::new();
+        }
+        self.grid
+            .empty_squares()
+            .into_iter()
+            .flat_map(|square| MARKS.iter().map(move |&mark| (square, mark)))
+            .collect()
+    }
+
+    pub fn winning_line(&self) -> Option<&'static [Coordinate]> {
+        completed_line(&self.grid, lines())
+    }
+
+    pub fn winner(&self) -> Option<Role> {
+        if self.winning_line().is_some() {
+            Some(Role::Order)
+        } else if self.grid.is_full() {
+            Some(Role::Chaos)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some()
+    }
+}
+
+impl Rules for OrderChaos {
+    type Move = Move;
+
+    fn to_move(&self) -> char {
+        self.to_move.sign()
+    }
+
+    fn legal_moves(&self) -> Vec<Move> {
+        OrderChaos::legal_moves(self)
+    }
+
+    fn play(&self, chosen: Move) -> Result<OrderChaos, String> {
+        OrderChaos::play(self, chosen)
+    }
+
+    fn status(&self) -> Status {
+        match OrderChaos::winner(self) {
+            Some(role) => Status::Won(role.sign()),
+            None => Status::Ongoing,
+        }
+    }
+}
+
+impl Variant for OrderChaos {
+    fn player_name(&self, sign: char) -> String {
+        Role::from_sign(sign).map_or_else(|| sign.to_string(), |role| role.title().to_string())
+    }
+
+    fn prompt(&self) -> String {
+        "enter a mark and a square, like X 2,3".to_string()
+    }
+
+    fn parse_move(&self, input: &str) -> Result<Move, String> {
+        parse_move(input)
+    }
+
+    fn format_move(&self, chosen: Move) -> String {
+        format_move(chosen)
+    }
+
+    fn render(&self) -> String {
+        render(self)
+    }
+
+    fn result(&self) -> String {
+        match OrderChaos::winner(self) {
+            Some(Role::Order) => "Five in a row, Order won!".to_string(),
+            _ => "The board is full, Chaos won!".to_string(),
+        }
+    }
+}
+
+fn lines() -> &'static [Vec<Coordinate>] {
+    static LINES: OnceLock<Vec<Vec<Coordinate>>> = OnceLock::new();
+    LINES.get_or_init(|| grid_lines(SIZE, SIZE, RUN))
+}
+
+// Reads `X 2,3`, `o 2 3` or `X2,3`, the square counted from 0 at the top
+// left.
+pub fn parse_move(input: &str) -> Result<Move, String> {
+    let input = input.trim();
+    let mark = match input.chars().next().map(|c| c.to_ascii_uppercase()) {
+        Some(mark) if MARKS.contains(&mark) => mark,
+        _ => return Err("Start the move with the mark, like X 2,3".to_string()),
+    };
+
+    let values = input[1..]
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .map(|value| value.parse::<usize>().ok().filter(|&v| v < SIZE))
+        .collect::<Vec<Option<usize>>>();
+    match values.as_slice() {
+        [Some(x), Some(y)] => Ok(((*x, *y), mark)),
+        [_, _] => Err(format!(
+            "Each value has to be a number from 0 to {}",
+            SIZE - 1
+        )),
+        _ => Err("Enter the mark and the square, like X 2,3".to_string()),
+    }
+}
+
+pub fn format_move(((x, y), mark): Move) -> String {
+    format!("{} {},{}", mark, x, y)
+}
+
+pub fn render(game: &OrderChaos) -> String {
+    let header = (0..SIZE).map(|x| x.to_string()).collect::<Vec<String>>();
+    let mut lines = vec![format!("  {}", header.join(" "))];
+    for y in 0..SIZE {
+        let row = (0..SIZE)
+            .map(|x| game.grid.get((x, y)).to_string())
+            .collect::<Vec<String>>();
+        lines.push(format!("{} {}", y, row.join(" ")));
+    }
+    lines.join("\n")
+}
+
+// Looks one move ahead on medium and two on hard, scoring the positions it
+// stops at by how far along the runs that can still be completed are. Order
+// wants that score high and Chaos wants it low. On easy it plays at random.
+pub struct OrderChaosRobot {
+    role: Role,
+    difficulty: Difficulty,
+    random: Random,
+}
+
+impl OrderChaosRobot {
+    pub fn new(role: Role) -> OrderChaosRobot {
+        OrderChaosRobot {
+            role,
+            difficulty: Difficulty::Hard,
+            random: Random::from_time(),
+        }
+    }
+
+    pub fn with_difficulty(self, difficulty: Difficulty) -> Self {
+        OrderChaosRobot { difficulty, ..self }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        OrderChaosRobot {
+            random: Random::new(seed),
+            ..self
+        }
+    }
+}
+
+impl VariantPlayer<OrderChaos> for OrderChaosRobot {
+    fn sign(&self) -> char {
+        self.role.sign()
+    }
+
+    fn choose_move(&mut self, game: &OrderChaos) -> Result<Option<Move>, String> {
+        let chosen = match self.difficulty {
+            Difficulty::Easy => self.random.choose(&game.legal_moves()).cloned(),
+            Difficulty::Medium => search(game, 1),
+            Difficulty::Hard => search(game, 2),
+        };
+        chosen
+            .map(Some)
+            .ok_or_else(|| "No choice found".to_string())
+    }
+}
+
+const WIN: i32 = 1_000_000;
+// What a run with 0 to 4 of the same mark and nothing else is worth to
+// Order.
+const RUN_WEIGHTS: [i32; RUN] = [0, 1, 4, 16, 64];
+
+// The best move for the player to move, looking `depth` moves ahead.
+pub fn search(game: &OrderChaos, depth: u32) -> Option<Move> {
+    let maximizing = game.to_move == Role::Order;
+    let mut best: Option<(Move, i32)> = None;
+
+    for chosen in game.legal_moves() {
+        let next = game.play(chosen).expect("legal moves can be played");
+        let score = value(&next, depth.max(1) - 1, -WIN * 2, WIN * 2);
+        let better = best.is_none_or(|(_, best_score)| {
+            if maximizing {
+                score > best_score
+            } else {
+                score < best_score
+            }
+        });
+        if better {
+            best = Some((chosen, score));
+        }
+    }
+    best.map(|(chosen, _)| chosen)
+}
+
+// How good the position is for Order, sooner wins counting for more.
+fn value(game: &OrderChaos, depth: u32, mut alpha: i32, mut beta: i32) -> i32 {
+    match game.winner() {
+        Some(Role::Order) => return WIN + depth as i32,
+        Some(Role::Chaos) => return -WIN - depth as i32,
+        None if depth == 0 => return evaluate(game),
+        None => {}
+    }
+
+    let maximizing = game.to_move == Role::Order;
+    let mut best = if maximizing { -WIN * 2 } else { WIN * 2 };
+    for chosen in game.legal_moves() {
+        let next = game.play(chosen).expect("legal moves can be played");
+        let score = value(&next, depth - 1, alpha, beta);
+        if maximizing {
+            best = best.max(score);
+            alpha = alpha.max(score);
+        } else {
+            best = best.min(score);
+            beta = beta.min(score);
+        }
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
+
+fn evaluate(game: &OrderChaos) -> i32 {
+    lines()
+        .iter()
+        .map(|line| {
+            let count = |mark: char| line.iter().filter(|&&c| game.grid.get(c) == mark).count();
+            match (count('X'), count('O')) {
+                (0, marks) | (marks, 0) => RUN_WEIGHTS[marks.min(RUN - 1)],
+                _ => 0,
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::Outcome;
+    use std::io;
+    use variant::play;
+
+    fn with_marks(marks: &[(Coordinate, char)]) -> OrderChaos {
+        let mut game = OrderChaos::new();
+        for &(square, mark) in marks {
+            game.grid = game.grid.add_value(square, mark).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn five_of_a_mark_wins_for_order() {
+        let marks = [(1, 1), (2, 2), (3, 3), (4, 4), (5, 5)].map(|square| (square, 'O'));
+        let game = with_marks(&marks);
+        assert_eq!(game.winner(), Some(Role::Order));
+        assert!(game.play(((0, 0), 'X')).is_err());
+    }
+
+    #[test]
+    fn mixed_marks_do_not_count() {
+        let marks = [(0, 0), (1, 0), (2, 0), (3, 0)].map(|square| (square, 'X'));
+        let game = with_marks(&marks).play(((4, 0), 'O')).unwrap();
+        assert_eq!(game.winner(), None);
+    }
+
+    #[test]
+    fn full_board_wins_for_chaos() {
+        let mut game = OrderChaos::new();
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                // Alternating pairs never line up five of a kind.
+                let mark = if (x / 2 + y) % 2 == 0 { 'X' } else { 'O' };
+                game.grid = game.grid.add_value((x, y), mark).unwrap();
+            }
+        }
+        assert_eq!(game.winner(), Some(Role::Chaos));
+    }
+
+    #[test]
+    fn parses_mark_and_square() {
+        assert_eq!(parse_move("X 2,3"), Ok(((2, 3), 'X')));
+        assert_eq!(parse_move("o 5 0"), Ok(((5, 0), 'O')));
+        assert_eq!(parse_move("O0,1"), Ok(((0, 1), 'O')));
+        assert!(parse_move("2,3").is_err());
+        assert!(parse_move("X 6,0").is_err());
+        assert_eq!(format_move(((4, 1), 'O')), "O 4,1");
+        assert_eq!(Role::from_name("chaos"), Some(Role::Chaos));
+    }
+
+    #[test]
+    fn renders_the_board() {
+        let game = with_marks(&[((0, 0), 'X'), ((5, 1), 'O')]);
+        let expected = "  0 1 2 3 4 5
+0 X - - - - -
+1 - - - - - O
+2 - - - - - -
+3 - - - - - -
+4 - - - - - -
+5 - - - - - -";
+        assert_eq!(render(&game), expected);
+    }
+
+    #[test]
+    fn order_completes_five() {
+        let marks = [(0, 2), (1, 2), (2, 2), (3, 2)].map(|square| (square, 'X'));
+        let game = with_marks(&marks);
+        let chosen = search(&game, 1).unwrap();
+        assert!(game.play(chosen).unwrap().winner() == Some(Role::Order));
+    }
+
+    #[test]
+    fn chaos_blocks_the_only_way_to_five() {
+        let marks = [(0, 2), (1, 2), (2, 2), (3, 2)].map(|square| (square, 'X'));
+        let mut game = with_marks(&marks);
+        game.to_move = Role::Chaos;
+        assert_eq!(search(&game, 2), Some(((4, 2), 'O')));
+    }
+
+    #[test]
+    fn chaos_robot_holds_off_a_random_order() {
+        let players: [Box<dyn VariantPlayer<OrderChaos>>; 2] = [
+            Box::new(OrderChaosRobot::new(Role::Chaos).with_difficulty(Difficulty::Medium)),
+            Box::new(
+                OrderChaosRobot::new(Role::Order)
+                    .with_difficulty(Difficulty::Easy)
+                    .with_seed(4),
+            ),
+        ];
+        let outcome = play(OrderChaos::new(), players, &mut io::sink()).unwrap();
+        assert_eq!(outcome, Outcome::Won('C'));
+    }
+}
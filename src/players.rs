@@ -1,13 +1,46 @@
 use board::Board;
 
-#[derive(Clone, Copy)]
-pub struct Player {
-    pub sign: char,
-    pub get_coordinate: fn(char, Board) -> Result<(usize, usize), String>,
+pub type Coordinate = (usize, usize);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    // Places the player's own mark.
+    Place(Coordinate),
+    // Places the given mark, which only wild tic-tac-toe allows to be the
+    // opponent's.
+    PlaceMark(Coordinate, char),
+    Abandon,
+    Resign,
+    OfferDraw,
+    Undo,
+    ShowBoard,
+    ShowHistory,
+    Save(String),
+}
+
+// Who moved is kept apart from the mark they placed, as in wild tic-tac-toe
+// either player may place either mark.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Move {
+    pub player: char,
+    pub mark: char,
+    pub coordinate: Coordinate,
 }
 
-impl PartialEq for Player {
-    fn eq(&self, other: &Player) -> bool {
-        self.sign == other.sign
+pub trait Player {
+    fn sign(&self) -> char;
+
+    fn choose_action(&mut self, board: Board) -> Result<Action, String>;
+
+    // Called when the opponent offers a draw instead of moving.
+    fn accept_draw(&mut self, _board: Board) -> bool {
+        false
+    }
+
+    // Asked between the games of a match.
+    fn play_again(&mut self) -> bool {
+        true
     }
+
+    fn game_over(&mut self, _board: Board, _message: &str) {}
 }
@@ -0,0 +1,688 @@
+use notation::{CellNumbers, Notation};
+use players::Coordinate;
+use random::Random;
+use robot_player::{get_opponent_sign, Difficulty};
+use rules::{Rules, Status};
+use variant::{Variant, VariantPlayer};
+use win_condition::lines;
+
+// In quantum tic-tac-toe every turn puts a spooky mark in two cells at once,
+// numbered by the turn it was placed on. Each spooky mark ties its two cells
+// together, and when a mark closes a cycle of those ties the other player
+// chooses which of its two cells it really is in. That collapses it into a
+// classical mark, and every other spooky mark in that cell has to be in its
+// other cell, and so on until the whole group is classical. Only classical
+// marks make lines.
+//
+// If one collapse gives both players a line, the line whose newest mark is
+// older wins a point and the other line half a point. When only one cell is
+// left, the last turn places a classical mark there.
+//
+// Cells are numbered 1 to 9 in reading order, so a spooky mark in both top
+// corners is `13`.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mark {
+    pub player: char,
+    pub turn: usize,
+}
+
+impl Mark {
+    // Spooky marks are lowercase, classical ones uppercase, like x3 and X3.
+    pub fn label(self, classical: bool) -> String {
+        let player = if classical {
+            self.player
+        } else {
+            self.player.to_ascii_lowercase()
+        };
+        format!("{}{}", player, self.turn)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Move {
+    Spooky(Coordinate, Coordinate),
+    Collapse(Coordinate),
+    Classical(Coordinate),
+}
+
+type Spooky = (Mark, Coordinate, Coordinate);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantum {
+    classical: [[Option<Mark>; 3]; 3],
+    // The marks still in two cells at once.
+    spooky: Vec<Spooky>,
+    // The mark that closed a cycle, waiting for the player to move to
+    // choose its cell before their own turn.
+    cycle: Option<Spooky>,
+    pub to_move: char,
+    turn: usize,
+}
+
+impl Default for Quantum {
+    fn default() -> Quantum {
+        Quantum::new()
+    }
+}
+
+impl Quantum {
+    pub fn new() -> Quantum {
+        Quantum {
+            classical: [[None; 3]; 3],
+            spooky: Vec::new(),
+            cycle: None,
+            to_move: 'X',
+            turn: 1,
+        }
+    }
+
+    pub fn classical(&self, (x, y): Coordinate) -> Option<Mark> {
+        self.classical[y][x]
+    }
+
+    pub fn spooky_marks(&self, cell: Coordinate) -> Vec<Mark> {
+        self.spooky
+            .iter()
+            .filter(|&&(_, a, b)| a == cell || b == cell)
+            .map(|&(mark, _, _)| mark)
+            .collect()
+    }
+
+    pub fn cycle(&self) -> Option<Spooky> {
+        self.cycle
+    }
+
+    // The cells without a classical mark.
+    pub fn open_cells(&self) -> Vec<Coordinate> {
+        (0..9)
+            .map(|i| (i % 3, i / 3))
+            .filter(|&cell| self.classical(cell).is_none())
+            .collect()
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.is_over() {
+            return Vec::new();
+        }
+        if let Some((_, a, b)) = self.cycle {
+            return vec![Move::Collapse(a), Move::Collapse(b)];
+        }
+
+        let open = self.open_cells();
+        if open.len() == 1 {
+            return vec![Move::Classical(open[0])];
+        }
+        let mut moves = Vec::new();
+        for (i, &a) in open.iter().enumerate() {
+            for &b in open[i + 1..].iter() {
+                moves.push(Move::Spooky(a, b));
+            }
+        }
+        moves
+    }
+
+    pub fn play(&self, chosen: Move) -> Result<Quantum, String> {
+        if self.is_over() {
+            return Err("The game is already over".to_string());
+        }
+        let mut next = self.clone();
+        let mark = Mark {
+            player: self.to_move,
+            turn: self.turn,
+        };
+
+        match (chosen, self.cycle) {
+            (Move::Collapse(cell), Some((cycled, a, b))) => {
+                if cell != a && cell != b {
+                    return Err(format!(
+                        "{} has to collapse into cell {} or {}",
+                        cycled.label(false),
+                        CellNumbers.format(a),
+                        CellNumbers.format(b)
+                    ));
+                }
+                next.collapse(cycled, cell);
+                next.cycle = None;
+                // Whoever chose the collapse still has their own turn.
+                return Ok(next);
+            }
+            (_, Some(_)) => return Err("Choose where the cycle collapses first".to_string()),
+            (Move::Collapse(_), None) => return Err("There is no cycle to collapse".to_string()),
+            (Move::Spooky(a, b), None) => {
+                if a == b {
+                    return Err("A spooky mark needs two different cells".to_string());
+                }
+                for &cell in [a, b].iter() {
+                    if cell.0 > 2 || cell.1 > 2 {
+                        return Err("The field is out of bounds".to_string());
+                    }
+                    if self.classical(cell).is_some() {
+                        return Err("The field is already taken".to_string());
+                    }
+                }
+                if self.connected(a, b) {
+                    next.cycle = Some((mark, a, b));
+                }
+                next.spooky.push((mark, a, b));
+            }
+            (Move::Classical(cell), None) => {
+                if self.open_cells() != [cell] {
+                    return Err("Classical marks only go in the last cell".to_string());
+                }
+                next.classical[cell.1][cell.0] = Some(mark);
+            }
+        }
+
+        next.turn += 1;
+        next.to_move = get_opponent_sign(self.to_move);
+        Ok(next)
+    }
+
+    // Whether spooky marks already tie the two cells together, so that one
+    // more between them closes a cycle.
+    fn connected(&self, from: Coordinate, to: Coordinate) -> bool {
+        let mut reached = vec![from];
+        let mut next = 0;
+        while next < reached.len() {
+            let cell = reached[next];
+            for &(_, a, b) in self.spooky.iter() {
+                let other = if a == cell {
+                    b
+                } else if b == cell {
+                    a
+                } else {
+                    continue;
+                };
+                if !reached.contains(&other) {
+                    reached.push(other);
+                }
+            }
+            next += 1;
+        }
+        reached.contains(&to)
+    }
+
+    // Puts the mark in the cell and pushes every other spooky mark there
+    // into its other cell, which can push out more marks in turn.
+    fn collapse(&mut self, mark: Mark, cell: Coordinate) {
+        let mut pending = vec![(mark, cell)];
+        while let Some((mark, cell)) = pending.pop() {
+            self.spooky.retain(|&(spooky, _, _)| spooky != mark);
+            self.classical[cell.1][cell.0] = Some(mark);
+
+            let (pushed, kept): (Vec<Spooky>, Vec<Spooky>) = self
+                .spooky
+                .iter()
+                .partition(|&&(_, a, b)| a == cell || b == cell);
+            self.spooky = kept;
+            for (other, a, b) in pushed {
+                pending.push((other, if a == cell { b } else { a }));
+            }
+        }
+    }
+
+    // The newest mark of the player's line with the oldest newest mark, or
+    // `None` if they have no line.
+    fn first_line(&self, player: char) -> Option<usize> {
+        lines()
+            .iter()
+            .filter_map(|line| {
+                let marks = line
+                    .iter()
+                    .map(|&cell| self.classical(cell).filter(|mark| mark.player == player))
+                    .collect::<Option<Vec<Mark>>>()?;
+                marks.iter().map(|mark| mark.turn).max()
+            })
+            .min()
+    }
+
+    // A point is two halves: a line is worth a point, or half a point if
+    // the other player got a line in the same collapse with older marks.
+    pub fn half_points(&self, player: char) -> u32 {
+        match (
+            self.first_line(player),
+            self.first_line(get_opponent_sign(player)),
+        ) {
+            (Some(mine), Some(theirs)) if mine > theirs => 1,
+            (Some(_), _) => 2,
+            (None, _) => 0,
+        }
+    }
+
+    pub fn winner(&self) -> Option<char> {
+        ['X', 'O']
+            .iter()
+            .cloned()
+            .find(|&player| self.half_points(player) == 2)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some() || self.open_cells().is_empty()
+    }
+}
+
+// A collapse is a move of its own here, made by the player who then moves
+// again.
+impl Rules for Quantum {
+    type Move = Move;
+
+    fn to_move(&self) -> char {
+        self.to_move
+    }
+
+    fn legal_moves(&self) -> Vec<Move> {
+        Quantum::legal_moves(self)
+    }
+
+    fn play(&self, chosen: Move) -> Result<Quantum, String> {
+        Quantum::play(self, chosen)
+    }
+
+    fn status(&self) -> Status {
+        match Quantum::winner(self) {
+            Some(sign) => Status::Won(sign),
+            None if Quantum::is_over(self) => Status::Draw,
+            None => Status::Ongoing,
+        }
+    }
+}
+
+impl Variant for Quantum {
+    fn prompt(&self) -> String {
+        match self.cycle {
+            Some((mark, a, b)) => format!(
+                "{} closed a cycle, choose whether it collapses into {} or {}",
+                mark.label(false),
+                CellNumbers.format(a),
+                CellNumbers.format(b)
+            ),
+            None if self.open_cells().len() == 1 => "enter the last cell".to_string(),
+            None => "enter two cells for your spooky mark, like 15".to_string(),
+        }
+    }
+
+    fn parse_move(&self, input: &str) -> Result<Move, String> {
+        parse_move(input, self)
+    }
+
+    fn format_move(&self, chosen: Move) -> String {
+        format_move(chosen)
+    }
+
+    fn render(&self) -> String {
+        render(self)
+    }
+
+    fn result(&self) -> String {
+        let winner = match Quantum::winner(self) {
+            Some(winner) => winner,
+            None => return "It's a tie!".to_string(),
+        };
+        let loser = get_opponent_sign(winner);
+        if self.half_points(loser) > 0 {
+            format!(
+                "Both players got a line, player {}'s came first and is worth a point, player {}'s half a point\nPlayer {} won!",
+                winner, loser, winner
+            )
+        } else {
+            format!("Player {} won!", winner)
+        }
+    }
+}
+
+// A spooky mark is two cells like `15`, a collapse or the last classical
+// mark one cell.
+pub fn parse_move(input: &str, game: &Quantum) -> Result<Move, String> {
+    let digits = input
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | ','))
+        .map(|c| c.to_string())
+        .collect::<Vec<String>>();
+
+    let expects_one = game.cycle.is_some() || game.open_cells().len() == 1;
+    match (digits.len(), expects_one) {
+        (1, true) => {
+            let cell = CellNumbers.parse(&digits[0])?;
+            if game.cycle.is_some() {
+                Ok(Move::Collapse(cell))
+            } else {
+                Ok(Move::Classical(cell))
+            }
+        }
+        (2, false) => Ok(Move::Spooky(
+            CellNumbers.parse(&digits[0])?,
+            CellNumbers.parse(&digits[1])?,
+        )),
+        (_, true) => Err("Enter a single cell from 1 to 9".to_string()),
+        (_, false) => Err("Enter the two cells for your spooky mark, like 15".to_string()),
+    }
+}
+
+pub fn format_move(chosen: Move) -> String {
+    match chosen {
+        Move::Spooky(a, b) => format!("{}{}", CellNumbers.format(a), CellNumbers.format(b)),
+        Move::Collapse(cell) => format!("collapse into {}", CellNumbers.format(cell)),
+        Move::Classical(cell) => CellNumbers.format(cell),
+    }
+}
+
+const CELL_WIDTH: usize = 10;
+
+// Every cell is three lines tall. A classical mark sits in the middle, and
+// spooky marks fill the lines three at a time.
+pub fn render(game: &Quantum) -> String {
+    let cell_lines = |cell: Coordinate| -> Vec<String> {
+        let text = match game.classical(cell) {
+            Some(mark) => vec![String::new(), mark.label(true), String::new()],
+            None => {
+                let labels = game
+                    .spooky_marks(cell)
+                    .iter()
+                    .map(|mark| mark.label(false))
+                    .collect::<Vec<String>>();
+                let mut text = labels
+                    .chunks(3)
+                    .map(|chunk| chunk.join(" "))
+                    .collect::<Vec<String>>();
+                text.resize(3, String::new());
+                text
+            }
+        };
+        text.iter()
+            .map(|line| format!("{:^width$}", line, width = CELL_WIDTH))
+            .collect()
+    };
+
+    let rows = (0..3)
+        .map(|y| {
+            let cells = (0..3)
+                .map(|x| cell_lines((x, y)))
+                .collect::<Vec<Vec<String>>>();
+            (0..3)
+                .map(|line| {
+                    let parts = cells.iter().map(|cell| cell[line].as_str());
+                    parts
+                        .collect::<Vec<&str>>()
+                        .join("|")
+                        .trim_end()
+                        .to_string()
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+        })
+        .collect::<Vec<String>>();
+    let divider = vec!["-".repeat(CELL_WIDTH); 3].join("+");
+    rows.join(&format!("\n{}\n", divider))
+}
+
+// Looks one move ahead on medium and two on hard, counting a collapse as a
+// move of its own. On easy it plays at random.
+pub struct QuantumRobot {
+    sign: char,
+    difficulty: Difficulty,
+    random: Random,
+}
+
+impl QuantumRobot {
+    pub fn new(sign: char) -> QuantumRobot {
+        QuantumRobot {
+            sign,
+            difficulty: Difficulty::Hard,
+            random: Random::from_time(),
+        }
+    }
+
+    pub fn with_difficulty(self, difficulty: Difficulty) -> Self {
+        QuantumRobot { difficulty, ..self }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        QuantumRobot {
+            random: Random::new(seed),
+            ..self
+        }
+    }
+}
+
+impl VariantPlayer<Quantum> for QuantumRobot {
+    fn sign(&self) -> char {
+        self.sign
+    }
+
+    fn choose_move(&mut self, game: &Quantum) -> Result<Option<Move>, String> {
+        let best = match self.difficulty {
+            Difficulty::Easy => game.legal_moves(),
+            Difficulty::Medium => search(game, 1),
+            Difficulty::Hard => search(game, 2),
+        };
+        self.random
+            .choose(&best)
+            .map(|&chosen| Some(chosen))
+            .ok_or_else(|| "No choice found".to_string())
+    }
+}
+
+const WIN: i32 = 1_000;
+
+// The equally good best moves for the player to move, looking `depth` moves
+// ahead after this one.
+pub fn search(game: &Quantum, depth: u32) -> Vec<Move> {
+    let mut best = Vec::new();
+    let mut best_score = i32::MIN;
+    for chosen in game.legal_moves() {
+        let next = game.play(chosen).expect("legal moves can be played");
+        let score = value_for(game.to_move, &next, depth);
+        if score > best_score {
+            best = vec![chosen];
+            best_score = score;
+        } else if score == best_score {
+            best.push(chosen);
+        }
+    }
+    best
+}
+
+// How good the position is for `player`. After a collapse the same player
+// moves again, so the sides don't simply alternate.
+fn value_for(player: char, game: &Quantum, depth: u32) -> i32 {
+    if game.is_over() {
+        let points = game.half_points(player) as i32;
+        let theirs = game.half_points(get_opponent_sign(player)) as i32;
+        return (points - theirs) * WIN;
+    }
+    if depth == 0 {
+        return evaluate(player, game);
+    }
+
+    let scores = game.legal_moves().into_iter().map(|chosen| {
+        let next = game.play(chosen).expect("legal moves can be played");
+        value_for(player, &next, depth - 1)
+    });
+    if game.to_move == player {
+        scores.max().unwrap_or(0)
+    } else {
+        scores.min().unwrap_or(0)
+    }
+}
+
+// Lines that only hold the player's classical marks, counted by how full
+// they are, against the same for the opponent.
+fn evaluate(player: char, game: &Quantum) -> i32 {
+    lines()
+        .iter()
+        .map(|line| {
+            let count = |sign: char| {
+                line.iter()
+                    .filter(|&&cell| game.classical(cell).is_some_and(|mark| mark.player == sign))
+                    .count() as i32
+            };
+            let (mine, theirs) = (count(player), count(get_opponent_sign(player)));
+            match (mine, theirs) {
+                (0, 0) => 0,
+                (mine, 0) => mine * mine,
+                (0, theirs) => -theirs * theirs,
+                _ => 0,
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::{Outcome, Transcript};
+    use variant::play;
+
+    fn cell(number: usize) -> Coordinate {
+        CellNumbers.parse(&number.to_string()).unwrap()
+    }
+
+    fn after(moves: &[&str]) -> Quantum {
+        moves.iter().fold(Quantum::new(), |game, input| {
+            let chosen = parse_move(input, &game).unwrap();
+            game.play(chosen).unwrap()
+        })
+    }
+
+    #[test]
+    fn spooky_marks_sit_in_two_cells() {
+        let game = after(&["15", "59"]);
+        assert_eq!(
+            game.spooky_marks(cell(5)),
+            vec![
+                Mark {
+                    player: 'X',
+                    turn: 1
+                },
+                Mark {
+                    player: 'O',
+                    turn: 2
+                }
+            ]
+        );
+        assert_eq!(game.classical(cell(5)), None);
+        assert_eq!(game.to_move, 'X');
+        assert!(game.play(Move::Spooky(cell(3), cell(3))).is_err());
+        assert!(game.play(Move::Collapse(cell(5))).is_err());
+    }
+
+    #[test]
+    fn closing_a_cycle_waits_for_a_collapse() {
+        let game = after(&["12", "23"]);
+        assert_eq!(game.cycle(), None);
+        let game = after(&["12", "23", "31"]);
+        let mark = Mark {
+            player: 'X',
+            turn: 3,
+        };
+        assert_eq!(game.cycle(), Some((mark, cell(3), cell(1))));
+        assert_eq!(game.to_move, 'O');
+        assert_eq!(
+            game.legal_moves(),
+            vec![Move::Collapse(cell(3)), Move::Collapse(cell(1))]
+        );
+        assert!(game.play(Move::Spooky(cell(5), cell(6))).is_err());
+        assert!(game.play(Move::Collapse(cell(2))).is_err());
+    }
+
+    #[test]
+    fn collapse_pushes_other_marks_out() {
+        // x3 hangs off the cycle and is pushed out of 1 as well.
+        let game = after(&["12", "23", "14", "31", "1"]);
+        let label = |number| game.classical(cell(number)).map(|mark| mark.label(true));
+        assert_eq!(label(1), Some("O4".to_string()));
+        assert_eq!(label(2), Some("X1".to_string()));
+        assert_eq!(label(3), Some("O2".to_string()));
+        assert_eq!(label(4), Some("X3".to_string()));
+        assert!(game.spooky_marks(cell(1)).is_empty());
+        // The player who chose the collapse moves next.
+        assert_eq!(game.to_move, 'X');
+        assert_eq!(game.cycle(), None);
+    }
+
+    #[test]
+    fn older_line_scores_a_whole_point() {
+        let mut game = Quantum::new();
+        let mark = |player, turn| Some(Mark { player, turn });
+        for (x, turn) in [(0, 1), (1, 3), (2, 5)].iter().cloned() {
+            game.classical[0][x] = mark('X', turn);
+        }
+        assert_eq!(game.winner(), Some('X'));
+        assert_eq!(game.half_points('X'), 2);
+
+        for (x, turn) in [(0, 2), (1, 4), (2, 6)].iter().cloned() {
+            game.classical[2][x] = mark('O', turn);
+        }
+        assert_eq!(game.half_points('X'), 2);
+        assert_eq!(game.half_points('O'), 1);
+
+        game.classical[0][2] = mark('X', 7);
+        assert_eq!(game.half_points('X'), 1);
+        assert_eq!(game.half_points('O'), 2);
+        assert_eq!(game.winner(), Some('O'));
+    }
+
+    #[test]
+    fn parses_by_what_the_turn_needs() {
+        assert_eq!(
+            parse_move("1 5", &Quantum::new()),
+            Ok(Move::Spooky(cell(1), cell(5)))
+        );
+        assert!(parse_move("5", &Quantum::new()).is_err());
+        let cycle = after(&["12", "21"]);
+        assert_eq!(parse_move("2", &cycle), Ok(Move::Collapse(cell(2))));
+        assert!(parse_move("12", &cycle).is_err());
+        assert_eq!(format_move(Move::Collapse(cell(2))), "collapse into 2");
+    }
+
+    #[test]
+    fn renders_spooky_and_classical_marks() {
+        let game = after(&["12", "21", "1", "35"]);
+        let expected = "          |          |    x3
+    O2    |    X1    |
+          |          |
+----------+----------+----------
+          |    x3    |
+          |          |
+          |          |
+----------+----------+----------
+          |          |
+          |          |
+          |          |";
+        assert_eq!(render(&game), expected);
+    }
+
+    #[test]
+    fn search_collapses_into_a_win() {
+        // X already has 1 and 2, and o6 closed a cycle with x5 between 3
+        // and 7. Putting o6 in 7 pushes x5 into 3.
+        let game = after(&["14", "14", "4", "25", "25", "5", "37", "37"]);
+        assert_eq!(game.to_move, 'X');
+        assert_eq!(search(&game, 0), vec![Move::Collapse(cell(7))]);
+        let next = game.play(Move::Collapse(cell(7))).unwrap();
+        assert_eq!(next.winner(), Some('X'));
+    }
+
+    #[test]
+    fn random_games_end_with_every_cell_classical_or_a_line() {
+        for seed in 0..10 {
+            let players: [Box<dyn VariantPlayer<Quantum>>; 2] = [
+                Box::new(
+                    QuantumRobot::new('X')
+                        .with_difficulty(Difficulty::Easy)
+                        .with_seed(seed),
+                ),
+                Box::new(
+                    QuantumRobot::new('O')
+                        .with_difficulty(Difficulty::Medium)
+                        .with_seed(seed + 10),
+                ),
+            ];
+            let mut output = Transcript::new();
+            let outcome = play(Quantum::new(), players, &mut output).unwrap();
+            assert!(matches!(outcome, Outcome::Won(_) | Outcome::Draw));
+        }
+    }
+}
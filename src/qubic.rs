@@ -0,0 +1,384 @@
+use robot_player::get_opponent_sign;
+use rules::{Rules, Status};
+use std::sync::OnceLock;
+use variant::{Variant, VariantPlayer};
+use win_condition::enumerate_lines;
+
+// Qubic is tic-tac-toe on a 4×4×4 cube: four in a row along any of the 76
+// lines through it wins, including the ones running between layers. Cells
+// are `x,y,z` counted from 0, with `z` picking the layer.
+
+pub type Point = (usize, usize, usize);
+
+const SIZE: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cube {
+    // Indexed `[z][y][x]`.
+    pub cells: [[[char; SIZE]; SIZE]; SIZE],
+    pub to_move: char,
+}
+
+impl Default for Cube {
+    fn default() -> Cube {
+        Cube::new()
+    }
+}
+
+impl Cube {
+    pub fn new() -> Cube {
+        Cube {
+            cells: [[['-'; SIZE]; SIZE]; SIZE],
+            to_move: 'X',
+        }
+    }
+
+    pub fn get(&self, (x, y, z): Point) -> char {
+        self.cells[z][y][x]
+    }
+
+    pub fn play(&self, point: Point) -> Result<Cube, String> {
+        if self.is_over() {
+            return Err("The game is already over".to_string());
+        }
+        let (x, y, z) = point;
+        if x >= SIZE || y >= SIZE || z >= SIZE {
+            return Err("The cell is out of bounds".to_string());
+        }
+        if self.get(point) != '-' {
+            return Err("The cell is already taken".to_string());
+        }
+
+        let mut next = *self;
+        next.cells[z][y][x] = self.to_move;
+        next.to_move = get_opponent_sign(self.to_move);
+        Ok(next)
+    }
+
+    pub fn legal_moves(&self) -> Vec<Point> {
+        if self.is_over() {
+            return Vec::new();
+        }
+        points().filter(|&point| self.get(point) == '-').collect()
+    }
+
+    pub fn winning_line(&self) -> Option<[Point; SIZE]> {
+        lines()
+            .iter()
+            .find(|line| {
+                let first = self.get(line[0]);
+                first != '-' && line.iter().all(|&point| self.get(point) == first)
+            })
+            .cloned()
+    }
+
+    pub fn winner(&self) -> Option<char> {
+        self.winning_line().map(|line| self.get(line[0]))
+    }
+
+    pub fn is_full(&self) -> bool {
+        points().all(|point| self.get(point) != '-')
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_full() || self.winner().is_some()
+    }
+}
+
+impl Rules for Cube {
+    type Move = Point;
+
+    fn to_move(&self) -> char {
+        self.to_move
+    }
+
+    fn legal_moves(&self) -> Vec<Point> {
+        Cube::legal_moves(self)
+    }
+
+    fn play(&self, chosen: Point) -> Result<Cube, String> {
+        Cube::play(self, chosen)
+    }
+
+    fn status(&self) -> Status {
+        match Cube::winner(self) {
+            Some(sign) => Status::Won(sign),
+            None if Cube::is_full(self) => Status::Draw,
+            None => Status::Ongoing,
+        }
+    }
+}
+
+impl Variant for Cube {
+    fn prompt(&self) -> String {
+        "enter a cell in the format x,y,z".to_string()
+    }
+
+    fn parse_move(&self, input: &str) -> Result<Point, String> {
+        parse_point(input)
+    }
+
+    fn format_move(&self, chosen: Point) -> String {
+        format_point(chosen)
+    }
+
+    fn render(&self) -> String {
+        render(self)
+    }
+}
+
+// All 76 lines: rows, columns and pillars, the diagonals of every plane and
+// the four running corner to corner through the middle of the cube.
+pub fn lines() -> &'static [[Point; SIZE]] {
+    static LINES: OnceLock<Vec<[Point; SIZE]>> = OnceLock::new();
+    LINES.get_or_init(|| {
+        enumerate_lines(SIZE, SIZE, 3)
+            .iter()
+            .map(|line| [0, 1, 2, 3].map(|step| (line[step][0], line[step][1], line[step][2])))
+            .collect()
+    })
+}
+
+fn points() -> impl Iterator<Item = Point> {
+    (0..SIZE * SIZE * SIZE).map(|i| (i % SIZE, i / SIZE % SIZE, i / (SIZE * SIZE)))
+}
+
+// `x,y,z`, `x y z` or `(x,y,z)` with each value from 0 to 3.
+pub fn parse_point(input: &str) -> Result<Point, String> {
+    let values = input
+        .trim()
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .map(|value| value.parse::<usize>().ok().filter(|&v| v < SIZE))
+        .collect::<Vec<Option<usize>>>();
+
+    match values.as_slice() {
+        [Some(x), Some(y), Some(z)] => Ok((*x, *y, *z)),
+        [_, _, _] => Err("Each value has to be a number from 0 to 3".to_string()),
+        _ => Err("Enter a cell as x,y,z, like 1,2,0".to_string()),
+    }
+}
+
+pub fn format_point((x, y, z): Point) -> String {
+    format!("{},{},{}", x, y, z)
+}
+
+// The layers side by side, the bottom one (`z` = 0) on the left.
+pub fn render(cube: &Cube) -> String {
+    let mut lines = vec![(0..SIZE)
+        .map(|z| format!("z={}    ", z))
+        .collect::<Vec<String>>()
+        .join("   ")
+        .trim_end()
+        .to_string()];
+
+    for y in 0..SIZE {
+        let row = (0..SIZE)
+            .map(|z| {
+                cube.cells[z][y]
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            })
+            .collect::<Vec<String>>();
+        lines.push(row.join("   "));
+    }
+    lines.join("\n")
+}
+
+// Looks `depth` moves ahead with alpha-beta pruning and scores the positions
+// it stops at by how many open lines each player has started.
+pub struct SearchPlayer {
+    sign: char,
+    depth: u32,
+}
+
+impl SearchPlayer {
+    pub fn new(sign: char, depth: u32) -> SearchPlayer {
+        SearchPlayer { sign, depth }
+    }
+}
+
+impl VariantPlayer<Cube> for SearchPlayer {
+    fn sign(&self) -> char {
+        self.sign
+    }
+
+    fn choose_move(&mut self, cube: &Cube) -> Result<Option<Point>, String> {
+        search(cube, self.depth)
+            .map(Some)
+            .ok_or_else(|| "No choice found".to_string())
+    }
+}
+
+const WIN: i32 = 1_000_000;
+// What an open line with 0 to 3 of a player's marks is worth to them.
+const LINE_WEIGHTS: [i32; SIZE] = [0, 1, 8, 64];
+
+pub fn search(cube: &Cube, depth: u32) -> Option<Point> {
+    let mut best: Option<(Point, i32)> = None;
+    let mut alpha = -WIN * 2;
+
+    for point in ordered_moves(cube) {
+        let next = cube.play(point).expect("legal moves can be played");
+        let score = -negamax(&next, depth.max(1) - 1, -WIN * 2, -alpha);
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((point, score));
+            alpha = alpha.max(score);
+        }
+    }
+    best.map(|(point, _)| point)
+}
+
+// The value of the position for the player to move.
+fn negamax(cube: &Cube, depth: u32, mut alpha: i32, beta: i32) -> i32 {
+    // The previous player just won, sooner is worse for us.
+    if cube.winner().is_some() {
+        return -WIN - depth as i32;
+    }
+    if cube.is_full() {
+        return 0;
+    }
+    if depth == 0 {
+        return evaluate(cube, cube.to_move);
+    }
+
+    for point in ordered_moves(cube) {
+        let next = cube.play(point).expect("legal moves can be played");
+        let score = -negamax(&next, depth - 1, -beta, -alpha);
+        if score >= beta {
+            return score;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+fn evaluate(cube: &Cube, sign: char) -> i32 {
+    lines()
+        .iter()
+        .map(|line| {
+            let mine = line.iter().filter(|&&p| cube.get(p) == sign).count();
+            let empty = line.iter().filter(|&&p| cube.get(p) == '-').count();
+            match (mine, SIZE - mine - empty) {
+                (0, 0) => 0,
+                (mine, 0) => LINE_WEIGHTS[mine],
+                (0, theirs) => -LINE_WEIGHTS[theirs],
+                _ => 0,
+            }
+        })
+        .sum()
+}
+
+// The corners and the eight center cells lie on seven lines each, the rest
+// on four, so trying them first makes pruning kick in sooner.
+fn ordered_moves(cube: &Cube) -> Vec<Point> {
+    static ORDER: OnceLock<Vec<Point>> = OnceLock::new();
+    let order = ORDER.get_or_init(|| {
+        let mut order = points().collect::<Vec<Point>>();
+        order.sort_by_key(|&point| {
+            let lines_through = lines().iter().filter(|line| line.contains(&point)).count();
+            std::cmp::Reverse(lines_through)
+        });
+        order
+    });
+
+    if cube.is_over() {
+        return Vec::new();
+    }
+    order
+        .iter()
+        .cloned()
+        .filter(|&point| cube.get(point) == '-')
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_marks(marks: &[(Point, char)]) -> Cube {
+        let mut cube = Cube::new();
+        for &((x, y, z), sign) in marks {
+            cube.cells[z][y][x] = sign;
+        }
+        cube
+    }
+
+    #[test]
+    fn cube_has_76_lines() {
+        assert_eq!(lines().len(), 76);
+        assert!(lines().contains(&[(0, 0, 0), (1, 1, 1), (2, 2, 2), (3, 3, 3)]));
+        assert!(lines().contains(&[(2, 1, 0), (2, 1, 1), (2, 1, 2), (2, 1, 3)]));
+        assert!(lines().contains(&[(3, 0, 0), (2, 0, 1), (1, 0, 2), (0, 0, 3)]));
+    }
+
+    #[test]
+    fn line_through_the_layers_wins() {
+        let marks = [(0, 3, 0), (1, 2, 1), (2, 1, 2), (3, 0, 3)].map(|p| (p, 'O'));
+        let cube = with_marks(&marks);
+        assert_eq!(cube.winner(), Some('O'));
+        assert!(cube.is_over());
+        assert!(cube.play((0, 0, 0)).is_err());
+    }
+
+    #[test]
+    fn taken_cells_are_rejected() {
+        let cube = Cube::new().play((1, 2, 3)).unwrap();
+        assert_eq!(cube.get((1, 2, 3)), 'X');
+        assert_eq!(cube.to_move, 'O');
+        assert!(cube.play((1, 2, 3)).is_err());
+        assert!(cube.play((4, 0, 0)).is_err());
+    }
+
+    #[test]
+    fn parses_three_coordinates() {
+        assert_eq!(parse_point("1,2,3"), Ok((1, 2, 3)));
+        assert_eq!(parse_point(" (0, 3, 1) "), Ok((0, 3, 1)));
+        assert_eq!(parse_point("3 3 0"), Ok((3, 3, 0)));
+        assert!(parse_point("1,2").is_err());
+        assert!(parse_point("1,2,4").is_err());
+        assert_eq!(format_point((3, 0, 2)), "3,0,2");
+    }
+
+    #[test]
+    fn renders_layers_side_by_side() {
+        let cube = with_marks(&[((1, 0, 0), 'X'), ((3, 3, 2), 'O')]);
+        let expected = "z=0       z=1       z=2       z=3
+- X - -   - - - -   - - - -   - - - -
+- - - -   - - - -   - - - -   - - - -
+- - - -   - - - -   - - - -   - - - -
+- - - -   - - - -   - - - O   - - - -";
+        assert_eq!(render(&cube), expected);
+    }
+
+    #[test]
+    fn search_takes_the_win() {
+        let mut cube = with_marks(&[
+            ((0, 0, 0), 'X'),
+            ((1, 1, 1), 'X'),
+            ((2, 2, 2), 'X'),
+            ((0, 1, 0), 'O'),
+            ((0, 2, 0), 'O'),
+            ((0, 3, 0), 'O'),
+        ]);
+        cube.to_move = 'X';
+        assert_eq!(search(&cube, 2), Some((3, 3, 3)));
+    }
+
+    #[test]
+    fn search_blocks_the_opponent() {
+        let mut cube = with_marks(&[
+            ((0, 1, 2), 'X'),
+            ((1, 1, 2), 'X'),
+            ((2, 1, 2), 'X'),
+            ((0, 0, 0), 'O'),
+            ((3, 3, 3), 'O'),
+        ]);
+        cube.to_move = 'O';
+        assert_eq!(search(&cube, 2), Some((3, 1, 2)));
+    }
+}
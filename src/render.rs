@@ -0,0 +1,295 @@
+use board::Board;
+use players::Coordinate;
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, IsTerminal};
+use win_condition::winning_line;
+
+const RESET: &str = "\x1b[0m";
+
+pub trait Render {
+    fn render(&self, board: Board, last_move: Option<Coordinate>) -> String;
+}
+
+pub struct Theme {
+    pub name: &'static str,
+    pub o: &'static str,
+    pub x: &'static str,
+    pub empty: &'static str,
+    pub last_move: &'static str,
+    pub winning_line: &'static str,
+}
+
+pub const THEMES: [Theme; 3] = [
+    Theme {
+        name: "default",
+        o: "\x1b[34m",
+        x: "\x1b[31m",
+        empty: "\x1b[2m",
+        last_move: "\x1b[1;4m",
+        winning_line: "\x1b[1;42m",
+    },
+    Theme {
+        name: "forest",
+        o: "\x1b[32m",
+        x: "\x1b[33m",
+        empty: "\x1b[2;37m",
+        last_move: "\x1b[1m",
+        winning_line: "\x1b[7m",
+    },
+    Theme {
+        name: "contrast",
+        o: "\x1b[1;97m",
+        x: "\x1b[1;93m",
+        empty: "\x1b[90m",
+        last_move: "\x1b[4m",
+        winning_line: "\x1b[1;97;41m",
+    },
+];
+
+pub fn find_theme(name: &str) -> Option<&'static Theme> {
+    THEMES.iter().find(|theme| theme.name == name)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    Classic,
+    Compact,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Charset {
+    Ascii,
+    Unicode,
+}
+
+#[derive(Clone)]
+pub struct TextRenderer {
+    pub layout: Layout,
+    pub charset: Charset,
+    pub theme: &'static Theme,
+    pub color: bool,
+}
+
+impl Default for TextRenderer {
+    fn default() -> TextRenderer {
+        TextRenderer::plain()
+    }
+}
+
+impl TextRenderer {
+    // The monochrome ASCII layout that `Board`'s `Display` impl has always used.
+    pub fn plain() -> TextRenderer {
+        TextRenderer {
+            layout: Layout::Classic,
+            charset: Charset::Ascii,
+            theme: &THEMES[0],
+            color: false,
+        }
+    }
+
+    // Picks colors and box drawing characters based on what the terminal
+    // looks capable of.
+    pub fn detect() -> TextRenderer {
+        TextRenderer {
+            layout: Layout::Classic,
+            charset: detect_charset(&[
+                env::var_os("LC_ALL"),
+                env::var_os("LC_CTYPE"),
+                env::var_os("LANG"),
+            ]),
+            theme: &THEMES[0],
+            color: should_use_color(env::var_os("NO_COLOR"), io::stdout().is_terminal()),
+        }
+    }
+
+    fn cell(&self, board: Board, coordinate: Coordinate, last_move: Option<Coordinate>) -> String {
+        let value = board.grid[coordinate.1][coordinate.0];
+        let shown = match (value, self.charset) {
+            ('-', Charset::Unicode) => '·',
+            (value, _) => value,
+        };
+
+        if !self.color {
+            return shown.to_string();
+        }
+
+        let mut style = match value {
+            'O' => self.theme.o,
+            'X' => self.theme.x,
+            '-' => self.theme.empty,
+            _ => "",
+        }
+        .to_string();
+        if last_move == Some(coordinate) {
+            style.push_str(self.theme.last_move);
+        }
+        if winning_line(board).is_some_and(|line| line.contains(&coordinate)) {
+            style.push_str(self.theme.winning_line);
+        }
+
+        if style.is_empty() {
+            shown.to_string()
+        } else {
+            format!("{}{}{}", style, shown, RESET)
+        }
+    }
+
+    fn render_classic(&self, cells: &[Vec<String>]) -> String {
+        let (vertical, horizontal, cross) = match self.charset {
+            Charset::Ascii => ("|", "_____", "|"),
+            Charset::Unicode => ("│", "─────", "┼"),
+        };
+        let spacer = format!("             {v}     {v}", v = vertical);
+        let separator = format!("        {h}{c}{h}{c}{h}", h = horizontal, c = cross);
+
+        let mut lines = vec!["          0     1     2".to_string()];
+        for (y, row) in cells.iter().enumerate() {
+            lines.push(spacer.clone());
+            lines.push(format!(
+                "    {}     {}  {v}  {}  {v}  {}",
+                y,
+                row[0],
+                row[1],
+                row[2],
+                v = vertical
+            ));
+            if y < 2 {
+                lines.push(separator.clone());
+            }
+        }
+        lines.push(format!("{}     ", spacer));
+        lines.join("\n")
+    }
+
+    fn render_compact(&self, cells: &[Vec<String>]) -> String {
+        let (vertical, separator) = match self.charset {
+            Charset::Ascii => ("|", "  -+-+-"),
+            Charset::Unicode => ("│", "  ─┼─┼─"),
+        };
+
+        let mut lines = vec!["  0 1 2".to_string()];
+        for (y, row) in cells.iter().enumerate() {
+            lines.push(format!("{} {}", y, row.join(vertical)));
+            if y < 2 {
+                lines.push(separator.to_string());
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+impl Render for TextRenderer {
+    fn render(&self, board: Board, last_move: Option<Coordinate>) -> String {
+        let cells = (0..3)
+            .map(|y| {
+                (0..3)
+                    .map(|x| self.cell(board, (x, y), last_move))
+                    .collect::<Vec<String>>()
+            })
+            .collect::<Vec<Vec<String>>>();
+
+        match self.layout {
+            Layout::Classic => self.render_classic(&cells),
+            Layout::Compact => self.render_compact(&cells),
+        }
+    }
+}
+
+pub fn should_use_color(no_color: Option<OsString>, is_terminal: bool) -> bool {
+    is_terminal && no_color.is_none_or(|value| value.is_empty())
+}
+
+pub fn detect_charset(locale_variables: &[Option<OsString>]) -> Charset {
+    let locale = locale_variables
+        .iter()
+        .filter_map(|value| value.as_ref())
+        .map(|value| value.to_string_lossy().to_lowercase())
+        .find(|value| !value.is_empty());
+
+    match locale {
+        Some(ref value) if value.contains("utf-8") || value.contains("utf8") => Charset::Unicode,
+        _ => Charset::Ascii,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> Board {
+        Board {
+            grid: [['O', '-', 'X'], ['-', 'O', '-'], ['X', '-', 'O']],
+        }
+    }
+
+    #[test]
+    fn plain_renderer_matches_display() {
+        let expected = "          0     1     2
+             |     |
+    0     O  |  -  |  X
+        _____|_____|_____
+             |     |
+    1     -  |  O  |  -
+        _____|_____|_____
+             |     |
+    2     X  |  -  |  O
+             |     |     ";
+        assert_eq!(TextRenderer::plain().render(board(), None), expected);
+    }
+
+    #[test]
+    fn compact_unicode_layout() {
+        let renderer = TextRenderer {
+            layout: Layout::Compact,
+            charset: Charset::Unicode,
+            ..TextRenderer::plain()
+        };
+        let expected = "  0 1 2\n0 O│·│X\n  ─┼─┼─\n1 ·│O│·\n  ─┼─┼─\n2 X│·│O";
+        assert_eq!(renderer.render(board(), None), expected);
+    }
+
+    #[test]
+    fn color_marks_players_and_highlights() {
+        let theme = find_theme("default").unwrap();
+        let renderer = TextRenderer {
+            layout: Layout::Compact,
+            color: true,
+            ..TextRenderer::plain()
+        };
+        let output = renderer.render(board(), Some((2, 0)));
+
+        assert!(output.contains(&format!("{}{}O{}", theme.o, theme.winning_line, RESET)));
+        assert!(output.contains(&format!("{}{}X{}", theme.x, theme.last_move, RESET)));
+        assert!(output.contains(&format!("{}-{}", theme.empty, RESET)));
+    }
+
+    #[test]
+    fn no_color_disables_color() {
+        assert!(should_use_color(None, true));
+        assert!(!should_use_color(Some(OsString::from("1")), true));
+        assert!(should_use_color(Some(OsString::new()), true));
+        assert!(!should_use_color(None, false));
+    }
+
+    #[test]
+    fn utf8_locale_enables_unicode() {
+        assert_eq!(
+            detect_charset(&[None, Some(OsString::from("en_US.UTF-8"))]),
+            Charset::Unicode
+        );
+        assert_eq!(
+            detect_charset(&[
+                Some(OsString::from("C")),
+                Some(OsString::from("en_US.UTF-8"))
+            ]),
+            Charset::Ascii
+        );
+        assert_eq!(detect_charset(&[None, None]), Charset::Ascii);
+    }
+
+    #[test]
+    fn unknown_theme_is_none() {
+        assert!(find_theme("neon").is_none());
+    }
+}
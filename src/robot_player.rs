@@ -1,35 +1,263 @@
 use board::Board;
+use game::RuleSet;
+use misere::{misere_coordinate, misere_value};
+use players::{Action, Coordinate, Move, Player};
+use random::Random;
+use rules::{safe_moves, winning_moves, Position, Rules};
+use tablebase;
+use wild::{wild_move, wild_value};
+use win_condition::lines;
+
+type MoveFunction = Vec<(&'static str, Box<dyn Fn(Board, char) -> Option<Coordinate>>)>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    // Plays a random free square.
+    Easy,
+    // Takes a win or blocks one when it sees it, otherwise plays randomly.
+    // In misère it just avoids completing a line itself, and in wild
+    // tic-tac-toe it avoids leaving a line for the opponent to complete.
+    Medium,
+    // Never loses.
+    Hard,
+}
 
-type Coordinate = (usize, usize);
-type MoveFunction = Vec<Box<Fn(Board, char) -> Option<Coordinate>>>;
+pub const DIFFICULTIES: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
 
-pub fn get_robot_coordinate(sign: char, board: Board) -> Result<Coordinate, String> {
-    let possible_moves: MoveFunction = vec![
-        Box::new(winning_move),
-        Box::new(block_winning_move),
-        Box::new(fork_move),
-        Box::new(block_fork_move),
-        Box::new(block_fork_opportunity_move),
-        Box::new(take_center_move),
-        Box::new(take_corner_move),
-        Box::new(take_side_move),
-    ];
+impl Difficulty {
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        DIFFICULTIES.iter().cloned().find(|d| d.name() == name)
+    }
+}
+
+// How the robot finds its moves on hard in standard games. Either way it
+// never loses; the other rule sets always search the game tree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strategy {
+    // Goes down the list in `get_robot_coordinate`: win, block, fork and so on.
+    Heuristic,
+    // Picks one of the best moves from the tablebase.
+    Tablebase,
+}
+
+pub const STRATEGIES: [Strategy; 2] = [Strategy::Heuristic, Strategy::Tablebase];
+
+impl Strategy {
+    pub fn name(self) -> &'static str {
+        match self {
+            Strategy::Heuristic => "heuristic",
+            Strategy::Tablebase => "tablebase",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Strategy> {
+        STRATEGIES.iter().cloned().find(|s| s.name() == name)
+    }
+}
+
+pub struct RobotPlayer {
+    pub sign: char,
+    pub difficulty: Difficulty,
+    pub rules: RuleSet,
+    pub strategy: Strategy,
+    random: Random,
+}
+
+impl RobotPlayer {
+    pub fn new(sign: char) -> RobotPlayer {
+        RobotPlayer {
+            sign,
+            difficulty: Difficulty::Hard,
+            rules: RuleSet::Standard,
+            strategy: Strategy::Heuristic,
+            random: Random::from_time(),
+        }
+    }
+
+    pub fn with_difficulty(self, difficulty: Difficulty) -> Self {
+        RobotPlayer { difficulty, ..self }
+    }
+
+    pub fn with_rules(self, rules: RuleSet) -> Self {
+        RobotPlayer { rules, ..self }
+    }
+
+    pub fn with_strategy(self, strategy: Strategy) -> Self {
+        RobotPlayer { strategy, ..self }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        RobotPlayer {
+            random: Random::new(seed),
+            ..self
+        }
+    }
+
+    fn random_move(&mut self, moves: &[Move]) -> Result<Move, String> {
+        self.random
+            .choose(moves)
+            .cloned()
+            .ok_or_else(|| "No choice found".to_string())
+    }
 
-    for possible_move in possible_moves {
-        if let Some(chosen_coordinate) = possible_move(board, sign) {
-            return Ok(chosen_coordinate);
+    // Takes a win when there is one, and otherwise avoids moves that lose
+    // straight away or let the opponent win straight away.
+    fn careful_move(&mut self, position: &Position) -> Result<Move, String> {
+        if let Some(&chosen) = winning_moves(position).first() {
+            return Ok(chosen);
         }
+        let safe = safe_moves(position);
+        if safe.is_empty() {
+            self.random_move(&position.legal_moves())
+        } else {
+            self.random_move(&safe)
+        }
+    }
+
+    fn best_move(&mut self, board: Board) -> Result<Move, String> {
+        let (coordinate, mark) = match (self.rules, self.strategy) {
+            (RuleSet::Wild, _) => wild_move(board)?,
+            (RuleSet::Standard, Strategy::Tablebase) => {
+                let entry = tablebase::lookup(board, self.sign)
+                    .ok_or("The board can't come up in a game")?;
+                let coordinate = *self.random.choose(&entry.moves).ok_or("No choice found")?;
+                (coordinate, self.sign)
+            }
+            (rules, _) => (best_coordinate(rules, self.sign, board)?, self.sign),
+        };
+        Ok(Move {
+            player: self.sign,
+            mark,
+            coordinate,
+        })
+    }
+}
+
+impl Player for RobotPlayer {
+    fn sign(&self) -> char {
+        self.sign
+    }
+
+    fn choose_action(&mut self, board: Board) -> Result<Action, String> {
+        let position = Position::new(board, self.sign).with_rules(self.rules);
+        let chosen = match self.difficulty {
+            Difficulty::Easy => self.random_move(&position.legal_moves()),
+            Difficulty::Medium => self.careful_move(&position),
+            Difficulty::Hard => self.best_move(board),
+        }?;
+
+        match self.rules {
+            RuleSet::Wild => Ok(Action::PlaceMark(chosen.coordinate, chosen.mark)),
+            _ => Ok(Action::Place(chosen.coordinate)),
+        }
+    }
+
+    // Only agrees once every line is blocked for the robot, so it can't win
+    // any more. In misère and wild games it agrees unless it's sure to win.
+    fn accept_draw(&mut self, board: Board) -> bool {
+        match self.rules {
+            RuleSet::Misere => return misere_value(board, get_opponent_sign(self.sign)) >= 0,
+            RuleSet::Wild => return wild_value(board) >= 0,
+            RuleSet::Standard => {}
+        }
+
+        lines().iter().all(|line| {
+            line.iter().any(|&(x, y)| {
+                let value = board.grid[y][x];
+                value != '-' && value != self.sign
+            })
+        })
+    }
+}
+
+// The square a perfect player would take under the given rules. In wild
+// games see `wild::wild_move` for the mark to go with it.
+pub fn best_coordinate(rules: RuleSet, sign: char, board: Board) -> Result<Coordinate, String> {
+    match rules {
+        RuleSet::Standard => get_robot_coordinate(sign, board),
+        RuleSet::Misere => misere_coordinate(sign, board),
+        RuleSet::Wild => wild_move(board).map(|(coordinate, _)| coordinate),
     }
+}
+
+pub fn get_robot_coordinate(sign: char, board: Board) -> Result<Coordinate, String> {
+    robot_rule(sign, board)
+        .map(|(_, coordinate)| coordinate)
+        .ok_or_else(|| "No choice found".to_string())
+}
+
+// The first of the robot's rules that gives a move, and the move.
+pub fn robot_rule(sign: char, board: Board) -> Option<(&'static str, Coordinate)> {
+    let possible_moves: MoveFunction = vec![
+        ("win", Box::new(winning_move)),
+        ("block a win", Box::new(block_winning_move)),
+        ("fork", Box::new(fork_move)),
+        ("block a fork", Box::new(block_fork_move)),
+        (
+            "block a fork opportunity",
+            Box::new(block_fork_opportunity_move),
+        ),
+        ("take the center", Box::new(take_center_move)),
+        ("take a corner", Box::new(take_corner_move)),
+        ("take a side", Box::new(take_side_move)),
+    ];
 
-    Err("No choice found".to_string())
+    possible_moves
+        .into_iter()
+        .find_map(|(name, possible_move)| possible_move(board, sign).map(|chosen| (name, chosen)))
 }
 
 fn block_winning_move(board: Board, sign: char) -> Option<Coordinate> {
     winning_move(board, get_opponent_sign(sign))
 }
 
+// With one square the opponent could fork on, take it. With more, no single
+// square blocks them all, so make two in a row instead, where the blocks
+// that follow don't end in a fork for the opponent.
 fn block_fork_move(board: Board, sign: char) -> Option<Coordinate> {
-    fork_move(board, get_opponent_sign(sign))
+    let opponent_sign = get_opponent_sign(sign);
+    let forks = fork_squares(board, opponent_sign);
+    if forks.len() < 2 {
+        return forks.first().cloned();
+    }
+
+    get_empty_squares(board, sign)
+        .into_iter()
+        .find(|&square| holds_off_forks(board.add_value(square, sign).unwrap(), sign))
+        .or(Some(forks[0]))
+}
+
+// Whether the opponent, about to move, can't get a fork while both players
+// are kept busy blocking each other's lines.
+fn holds_off_forks(board: Board, sign: char) -> bool {
+    let opponent_sign = get_opponent_sign(sign);
+    if winning_move(board, opponent_sign).is_some() {
+        return false;
+    }
+    if two_winning_moves(board, sign) {
+        return true;
+    }
+
+    let block = match winning_move(board, sign) {
+        Some(block) => block,
+        None => return fork_squares(board, opponent_sign).is_empty(),
+    };
+    let answered = board.add_value(block, opponent_sign).unwrap();
+    if two_winning_moves(answered, opponent_sign) {
+        return false;
+    }
+    match winning_move(answered, opponent_sign) {
+        Some(square) => holds_off_forks(answered.add_value(square, sign).unwrap(), sign),
+        None => true,
+    }
 }
 
 fn take_center_move(board: Board, _sign: char) -> Option<Coordinate> {
@@ -43,25 +271,17 @@ fn take_center_move(board: Board, _sign: char) -> Option<Coordinate> {
 fn take_corner_move(board: Board, sign: char) -> Option<Coordinate> {
     let empty_squares: Vec<Coordinate> = get_empty_squares(board, sign);
 
-    for square in empty_squares {
-        if square == (0, 0) || square == (0, 2) || square == (2, 0) || square == (2, 2) {
-            return Some(square);
-        }
-    }
-
-    None
+    empty_squares.into_iter().find(|&square| {
+        square == (0, 0) || square == (0, 2) || square == (2, 0) || square == (2, 2)
+    })
 }
 
 fn take_side_move(board: Board, sign: char) -> Option<Coordinate> {
     let empty_squares: Vec<Coordinate> = get_empty_squares(board, sign);
 
-    for square in empty_squares {
-        if square == (1, 0) || square == (0, 1) || square == (2, 1) || square == (1, 2) {
-            return Some(square);
-        }
-    }
-
-    None
+    empty_squares.into_iter().find(|&square| {
+        square == (1, 0) || square == (0, 1) || square == (2, 1) || square == (1, 2)
+    })
 }
 
 fn block_fork_opportunity_move(board: Board, sign: char) -> Option<Coordinate> {
@@ -99,7 +319,7 @@ fn block_fork_opportunity_move(board: Board, sign: char) -> Option<Coordinate> {
     }
 }
 
-fn get_opponent_sign(sign: char) -> char {
+pub fn get_opponent_sign(sign: char) -> char {
     if sign == 'X' {
         'O'
     } else {
@@ -108,16 +328,17 @@ fn get_opponent_sign(sign: char) -> char {
 }
 
 fn fork_move(board: Board, sign: char) -> Option<Coordinate> {
-    let empty_squares: Vec<Coordinate> = get_empty_squares(board, sign);
-
-    for square in empty_squares {
-        let attempted_grid = board.add_value(square, sign).unwrap();
-        if two_winning_moves(attempted_grid, sign) {
-            return Some(square);
-        }
-    }
+    fork_squares(board, sign).first().cloned()
+}
 
-    None
+fn fork_squares(board: Board, sign: char) -> Vec<Coordinate> {
+    get_empty_squares(board, sign)
+        .into_iter()
+        .filter(|&square| {
+            let attempted_grid = board.add_value(square, sign).unwrap();
+            two_winning_moves(attempted_grid, sign)
+        })
+        .collect()
 }
 
 fn two_winning_moves(board: Board, sign: char) -> bool {
@@ -162,10 +383,12 @@ fn winning_move(board: Board, sign: char) -> Option<Coordinate> {
 
 fn get_winning_row_coordinate(board: Board, sign: char) -> Option<Coordinate> {
     for (y, row) in board.grid.iter().enumerate() {
-        let empty_value = row.clone().iter().position(|s| *s == '-');
+        let empty_value = row.iter().position(|s| *s == '-');
 
-        if empty_value.is_some() && row.iter().filter(|&v| *v == sign).count() == 2 {
-            return Some((empty_value.unwrap(), y));
+        if let Some(x) = empty_value {
+            if row.iter().filter(|&v| *v == sign).count() == 2 {
+                return Some((x, y));
+            }
         }
     }
     None
@@ -174,10 +397,7 @@ fn get_winning_row_coordinate(board: Board, sign: char) -> Option<Coordinate> {
 fn get_winning_column_coordinate(board: Board, sign: char) -> Option<Coordinate> {
     let transposed = board.transpose();
 
-    match get_winning_row_coordinate(transposed, sign) {
-        Some(coordinate) => Some((coordinate.1, coordinate.0)),
-        None => None,
-    }
+    get_winning_row_coordinate(transposed, sign).map(|coordinate| (coordinate.1, coordinate.0))
 }
 
 fn get_winning_diagonal_coordinate(board: Board, sign: char) -> Option<Coordinate> {
@@ -188,16 +408,20 @@ fn get_winning_diagonal_coordinate(board: Board, sign: char) -> Option<Coordinat
         bottom_top_diagonal.push(board.grid[x][2 - x]);
     }
 
-    let empty_value = top_bottom_diagonal.clone().iter().position(|s| *s == '-');
+    let empty_value = top_bottom_diagonal.iter().position(|s| *s == '-');
 
-    if empty_value.is_some() && top_bottom_diagonal.iter().filter(|&v| *v == sign).count() == 2 {
-        return Some((empty_value.unwrap(), empty_value.unwrap()));
+    if let Some(i) = empty_value {
+        if top_bottom_diagonal.iter().filter(|&v| *v == sign).count() == 2 {
+            return Some((i, i));
+        }
     }
 
-    let empty_value = bottom_top_diagonal.clone().iter().position(|s| *s == '-');
+    let empty_value = bottom_top_diagonal.iter().position(|s| *s == '-');
 
-    if empty_value.is_some() && bottom_top_diagonal.iter().filter(|&v| *v == sign).count() == 2 {
-        return Some((2 - empty_value.unwrap(), empty_value.unwrap()));
+    if let Some(i) = empty_value {
+        if bottom_top_diagonal.iter().filter(|&v| *v == sign).count() == 2 {
+            return Some((2 - i, i));
+        }
     }
 
     None
@@ -206,23 +430,24 @@ fn get_winning_diagonal_coordinate(board: Board, sign: char) -> Option<Coordinat
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rules::Value;
 
     #[test]
     fn empty_board_is_not_two_winning_moves() {
         let grid = [['-'; 3]; 3];
-        assert_eq!(two_winning_moves(Board { grid }, 'O'), false);
+        assert!(!two_winning_moves(Board { grid }, 'O'));
     }
 
     #[test]
     fn simple_fork_is_two_winning_moves() {
         let grid = [['O', '-', '-'], ['X', 'O', '-'], ['O', '-', '-']];
-        assert_eq!(two_winning_moves(Board { grid }, 'O'), true);
+        assert!(two_winning_moves(Board { grid }, 'O'));
     }
 
     #[test]
     fn one_winning_move_is_not_two_winning_moves() {
         let grid = [['O', '-', '-'], ['O', '-', '-'], ['-', '-', '-']];
-        assert_eq!(two_winning_moves(Board { grid }, 'O'), false);
+        assert!(!two_winning_moves(Board { grid }, 'O'));
     }
 
     #[test]
@@ -290,10 +515,21 @@ mod tests {
 
     #[test]
     fn prevents_fork_opportunity_if_no_win_or_block() {
+        // A corner would force X to block in the other corner, which forks.
         let grid = [['X', '-', '-'], ['-', 'O', '-'], ['-', '-', 'X']];
         let coordinate = get_robot_coordinate('O', Board { grid }).unwrap();
-        let good_choices: Vec<Coordinate> = vec![(0, 2), (2, 0)];
-        assert!(good_choices.iter().any(|x| *x == coordinate));
+        let good_choices: Vec<Coordinate> = vec![(1, 0), (0, 1), (2, 1), (1, 2)];
+        assert!(good_choices.contains(&coordinate));
+    }
+
+    #[test]
+    fn makes_two_in_a_row_that_does_not_lead_to_a_fork() {
+        // X could fork in four places. Taking the top left corner makes X
+        // block in the bottom left, which threatens the bottom row, and once
+        // O blocks that X forks in the top right.
+        let grid = [['-', 'X', '-'], ['O', '-', '-'], ['-', '-', 'X']];
+        let coordinate = get_robot_coordinate('O', Board { grid }).unwrap();
+        assert_eq!(coordinate, (1, 1));
     }
 
     #[test]
@@ -308,7 +544,7 @@ mod tests {
         let grid = [['-', '-', '-'], ['-', 'X', '-'], ['-', '-', '-']];
         let coordinate = get_robot_coordinate('O', Board { grid }).unwrap();
         let good_choices: Vec<Coordinate> = vec![(0, 0), (2, 0), (2, 2), (0, 2)];
-        assert_eq!(good_choices.iter().any(|x| *x == coordinate), true);
+        assert!(good_choices.contains(&coordinate));
     }
 
     #[test]
@@ -326,4 +562,141 @@ mod tests {
         let coordinate = get_robot_coordinate('O', Board { grid }).unwrap();
         assert_eq!(coordinate, (1, 2));
     }
+
+    #[test]
+    fn accepts_draw_only_when_it_cannot_win() {
+        let mut robot = RobotPlayer::new('X');
+        let open = [['O', '-', '-'], ['-', 'X', '-'], ['-', '-', '-']];
+        assert!(!robot.accept_draw(Board { grid: open }));
+
+        let blocked = [['O', 'X', 'O'], ['O', 'X', '-'], ['X', 'O', '-']];
+        assert!(robot.accept_draw(Board { grid: blocked }));
+    }
+
+    #[test]
+    fn easy_robot_plays_a_free_square() {
+        let grid = [['O', 'X', 'O'], ['X', '-', 'X'], ['O', 'X', 'O']];
+        let mut robot = RobotPlayer::new('X')
+            .with_difficulty(Difficulty::Easy)
+            .with_seed(3);
+        assert_eq!(
+            robot.choose_action(Board { grid }),
+            Ok(Action::Place((1, 1)))
+        );
+    }
+
+    #[test]
+    fn medium_robot_blocks_a_win() {
+        let grid = [['O', 'O', '-'], ['-', 'X', '-'], ['-', '-', '-']];
+        for seed in 0..10 {
+            let mut robot = RobotPlayer::new('X')
+                .with_difficulty(Difficulty::Medium)
+                .with_seed(seed);
+            assert_eq!(
+                robot.choose_action(Board { grid }),
+                Ok(Action::Place((2, 0)))
+            );
+        }
+    }
+
+    #[test]
+    fn difficulty_names_round_trip() {
+        for difficulty in DIFFICULTIES.iter() {
+            assert_eq!(Difficulty::from_name(difficulty.name()), Some(*difficulty));
+        }
+        assert_eq!(Difficulty::from_name("nightmare"), None);
+    }
+
+    #[test]
+    fn hard_misere_robot_avoids_completing_a_line() {
+        let grid = [['X', 'X', '-'], ['O', '-', 'O'], ['-', 'O', 'X']];
+        let mut robot = RobotPlayer::new('X').with_rules(RuleSet::Misere);
+        assert_eq!(
+            robot.choose_action(Board { grid }),
+            Ok(Action::Place((0, 2)))
+        );
+    }
+
+    #[test]
+    fn medium_misere_robot_plays_safe_squares() {
+        let grid = [['X', 'X', '-'], ['O', 'O', '-'], ['-', '-', '-']];
+        for seed in 0..10 {
+            let mut robot = RobotPlayer::new('X')
+                .with_difficulty(Difficulty::Medium)
+                .with_rules(RuleSet::Misere)
+                .with_seed(seed);
+            assert_ne!(
+                robot.choose_action(Board { grid }),
+                Ok(Action::Place((2, 0)))
+            );
+        }
+    }
+
+    #[test]
+    fn wild_robot_completes_a_line_in_either_mark() {
+        let grid = [['O', 'O', '-'], ['X', '-', '-'], ['X', '-', '-']];
+        for &difficulty in [Difficulty::Medium, Difficulty::Hard].iter() {
+            let mut robot = RobotPlayer::new('X')
+                .with_difficulty(difficulty)
+                .with_rules(RuleSet::Wild);
+            assert_eq!(
+                robot.choose_action(Board { grid }),
+                Ok(Action::PlaceMark((2, 0), 'O'))
+            );
+        }
+    }
+
+    // The kind of result with perfect play, ignoring how long it takes.
+    fn outcome(value: Value) -> i8 {
+        match value {
+            Value::Win(_) => 1,
+            Value::Draw => 0,
+            Value::Loss(_) => -1,
+        }
+    }
+
+    // The tablebase knows every position, so it can check the heuristics in
+    // positions no game against the robot ever reaches. The heuristics never
+    // lose a position that can be held, but they sometimes settle for a draw
+    // after an opponent's mistake.
+    #[test]
+    fn never_loses_a_position_the_tablebase_holds() {
+        for position in tablebase::reachable() {
+            let board = position.board;
+            for &sign in ['X', 'O'].iter() {
+                let expected = match tablebase::lookup(board, sign) {
+                    Some(ref entry) if !entry.moves.is_empty() => entry.value,
+                    _ => continue,
+                };
+                let square = get_robot_coordinate(sign, board).unwrap();
+                let next = board.add_value(square, sign).unwrap();
+                let reached = tablebase::lookup(next, get_opponent_sign(sign))
+                    .unwrap()
+                    .value
+                    .before();
+                assert!(
+                    outcome(reached) >= outcome(expected).min(0),
+                    "{} playing {:?} on {} loses",
+                    sign,
+                    square,
+                    board.to_cells()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tablebase_strategy_plays_a_best_move() {
+        let grid = [['X', 'X', '-'], ['O', 'O', '-'], ['-', '-', '-']];
+        let mut robot = RobotPlayer::new('X')
+            .with_strategy(Strategy::Tablebase)
+            .with_seed(4);
+        assert_eq!(
+            robot.choose_action(Board { grid }),
+            Ok(Action::Place((2, 0)))
+        );
+        for &strategy in STRATEGIES.iter() {
+            assert_eq!(Strategy::from_name(strategy.name()), Some(strategy));
+        }
+    }
 }
@@ -0,0 +1,361 @@
+use board::Board;
+use game::{is_full_board, RuleSet};
+use players::{Coordinate, Move};
+use robot_player::get_opponent_sign;
+use std::collections::HashMap;
+use wild::MARKS;
+use win_condition::winning_line;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Ongoing,
+    Won(char),
+    Draw,
+}
+
+// The outcome with perfect play for the player to move, and how many moves
+// it takes to get there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Win(u32),
+    Loss(u32),
+    Draw,
+}
+
+impl Value {
+    // The value for the player who moved into a position with this value.
+    pub fn before(self) -> Value {
+        match self {
+            Value::Win(moves) => Value::Loss(moves + 1),
+            Value::Loss(moves) => Value::Win(moves + 1),
+            Value::Draw => Value::Draw,
+        }
+    }
+
+    // Wins fast, holds a draw if it can't, and otherwise loses slowly.
+    pub fn rank(self) -> (u8, i64) {
+        match self {
+            Value::Win(moves) => (2, -(moves as i64)),
+            Value::Draw => (1, 0),
+            Value::Loss(moves) => (0, moves as i64),
+        }
+    }
+}
+
+// What the game loop and the general robots need to know about a game:
+// whose turn it is, what they can do, and how it ended. Positions are
+// values, so trying a move gives a new position and leaves the old one as
+// it was.
+pub trait Rules: Clone {
+    type Move: Copy + PartialEq;
+
+    fn to_move(&self) -> char;
+
+    // Empty once the game is over.
+    fn legal_moves(&self) -> Vec<Self::Move>;
+
+    fn play(&self, chosen: Self::Move) -> Result<Self, String>;
+
+    // The position before the last move, for the games that keep track of
+    // their moves.
+    fn undo(&self) -> Option<Self> {
+        None
+    }
+
+    fn status(&self) -> Status;
+
+    fn winner(&self) -> Option<char> {
+        match self.status() {
+            Status::Won(sign) => Some(sign),
+            _ => None,
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.status() != Status::Ongoing
+    }
+}
+
+// The moves that win on the spot.
+pub fn winning_moves<R: Rules>(position: &R) -> Vec<R::Move> {
+    let sign = position.to_move();
+    position
+        .legal_moves()
+        .into_iter()
+        .filter(|&chosen| {
+            position
+                .play(chosen)
+                .is_ok_and(|next| next.winner() == Some(sign))
+        })
+        .collect()
+}
+
+// The moves that neither lose on the spot nor leave the opponent a move
+// that wins on the spot. Winning moves are always safe.
+pub fn safe_moves<R: Rules>(position: &R) -> Vec<R::Move> {
+    let sign = position.to_move();
+    position
+        .legal_moves()
+        .into_iter()
+        .filter(|&chosen| {
+            position.play(chosen).is_ok_and(|next| match next.status() {
+                Status::Won(winner) => winner == sign,
+                Status::Draw => true,
+                Status::Ongoing => winning_moves(&next).is_empty(),
+            })
+        })
+        .collect()
+}
+
+// How good the position is for the player to move with perfect play:
+// positive when they win, negative when they lose and zero for a draw. Wins
+// that come sooner and losses that come later are further from zero. The
+// whole game tree of a 3x3 board is small enough to search with a cache.
+pub fn negamax(position: &Position) -> i32 {
+    search(position, &mut HashMap::new())
+}
+
+// The first of the best moves, if the game isn't over.
+pub fn negamax_move(position: &Position) -> Option<Move> {
+    let mut cache = HashMap::new();
+    let mut best = None;
+
+    for chosen in position.legal_moves() {
+        let next = position.play(chosen).expect("legal moves can be played");
+        let score = -search(&next, &mut cache);
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((chosen, score));
+        }
+    }
+    best.map(|(chosen, _)| chosen)
+}
+
+// The rule set is the same throughout a search, so the board and the player
+// to move are enough to tell positions apart.
+fn search(position: &Position, cache: &mut HashMap<([[char; 3]; 3], char), i32>) -> i32 {
+    let key = (position.board.grid, position.to_move);
+    if let Some(&score) = cache.get(&key) {
+        return score;
+    }
+
+    let remaining = position.board.to_cells().matches('-').count() as i32;
+    let score = match position.status() {
+        Status::Won(winner) if winner == position.to_move => remaining + 1,
+        Status::Won(_) => -(remaining + 1),
+        Status::Draw => 0,
+        Status::Ongoing => position
+            .legal_moves()
+            .into_iter()
+            .map(|chosen| {
+                let next = position.play(chosen).expect("legal moves can be played");
+                -search(&next, cache)
+            })
+            .max()
+            .unwrap_or(0),
+    };
+
+    cache.insert(key, score);
+    score
+}
+
+// Tic-tac-toe on the 3x3 board under one of the rule sets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    pub board: Board,
+    pub to_move: char,
+    pub rules: RuleSet,
+    // The board `history` was played from, so moves can be taken back.
+    start: Board,
+    history: Vec<Move>,
+}
+
+impl Position {
+    pub fn new(board: Board, to_move: char) -> Position {
+        Position {
+            board,
+            to_move,
+            rules: RuleSet::Standard,
+            start: board,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn with_rules(self, rules: RuleSet) -> Self {
+        Position { rules, ..self }
+    }
+
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    pub fn last_move(&self) -> Option<Move> {
+        self.history.last().cloned()
+    }
+
+    // Places the mark of the player to move.
+    pub fn place(&self, coordinate: Coordinate) -> Result<Position, String> {
+        self.play(Move {
+            player: self.to_move,
+            mark: self.to_move,
+            coordinate,
+        })
+    }
+}
+
+impl Rules for Position {
+    type Move = Move;
+
+    fn to_move(&self) -> char {
+        self.to_move
+    }
+
+    fn legal_moves(&self) -> Vec<Move> {
+        if self.is_over() {
+            return Vec::new();
+        }
+        let marks = match self.rules {
+            RuleSet::Wild => MARKS.to_vec(),
+            _ => vec![self.to_move],
+        };
+
+        let mut moves = Vec::new();
+        for coordinate in (0..9).map(|i| (i % 3, i / 3)) {
+            if self.board.grid[coordinate.1][coordinate.0] != '-' {
+                continue;
+            }
+            for &mark in marks.iter() {
+                moves.push(Move {
+                    player: self.to_move,
+                    mark,
+                    coordinate,
+                });
+            }
+        }
+        moves
+    }
+
+    fn play(&self, chosen: Move) -> Result<Position, String> {
+        let opponent = get_opponent_sign(self.to_move);
+        if chosen.player != self.to_move {
+            return Err(format!("It's player {}'s turn", self.to_move));
+        }
+        if chosen.mark != self.to_move && self.rules != RuleSet::Wild {
+            return Err(format!(
+                "You can only place your own mark, {}",
+                self.to_move
+            ));
+        }
+        if chosen.mark != self.to_move && chosen.mark != opponent {
+            return Err(format!(
+                "The mark has to be {} or {}",
+                self.to_move, opponent
+            ));
+        }
+        if self.is_over() {
+            return Err("The game is already over".to_string());
+        }
+
+        let mut next = self.clone();
+        next.board = self.board.add_value(chosen.coordinate, chosen.mark)?;
+        next.to_move = opponent;
+        next.history.push(chosen);
+        Ok(next)
+    }
+
+    fn undo(&self) -> Option<Position> {
+        let last = self.last_move()?;
+        let mut previous = self.clone();
+        previous.history.pop();
+        previous.board = previous.history.iter().fold(self.start, |board, m| {
+            board.add_value(m.coordinate, m.mark).unwrap_or(board)
+        });
+        previous.to_move = last.player;
+        Some(previous)
+    }
+
+    // Whoever made the line just moved. They win, except in misère where
+    // they lose.
+    fn status(&self) -> Status {
+        if winning_line(self.board).is_some() {
+            match self.rules {
+                RuleSet::Misere => Status::Won(self.to_move),
+                RuleSet::Standard | RuleSet::Wild => Status::Won(get_opponent_sign(self.to_move)),
+            }
+        } else if is_full_board(self.board) {
+            Status::Draw
+        } else {
+            Status::Ongoing
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn after(rules: RuleSet, squares: &[Coordinate]) -> Position {
+        squares.iter().fold(
+            Position::new(Board::new(), 'X').with_rules(rules),
+            |position, &square| position.place(square).unwrap(),
+        )
+    }
+
+    #[test]
+    fn position_alternates_and_finds_the_winner() {
+        let position = Position::new(Board::new(), 'X');
+        assert_eq!(position.legal_moves().len(), 9);
+
+        let position = after(RuleSet::Standard, &[(0, 0), (0, 1), (1, 0), (1, 1), (2, 0)]);
+        assert_eq!(position.status(), Status::Won('X'));
+        assert!(position.legal_moves().is_empty());
+        assert!(position.place((2, 2)).is_err());
+    }
+
+    #[test]
+    fn misere_line_loses_and_wild_offers_both_marks() {
+        let position = after(RuleSet::Misere, &[(0, 0), (0, 1), (1, 0), (1, 1), (2, 0)]);
+        assert_eq!(position.status(), Status::Won('O'));
+
+        let position = after(RuleSet::Wild, &[(0, 0)]);
+        assert_eq!(position.legal_moves().len(), 16);
+        let mark = Move {
+            player: 'O',
+            mark: 'X',
+            coordinate: (1, 0),
+        };
+        assert!(position.play(mark).is_ok());
+        assert!(after(RuleSet::Standard, &[(0, 0)]).play(mark).is_err());
+    }
+
+    #[test]
+    fn undo_restores_the_previous_position() {
+        let position = after(RuleSet::Standard, &[(1, 1), (0, 0)]);
+        let previous = position.undo().unwrap();
+        assert_eq!(previous, after(RuleSet::Standard, &[(1, 1)]));
+        assert_eq!(previous.to_move, 'O');
+        assert_eq!(previous.undo().unwrap().undo(), None);
+    }
+
+    #[test]
+    fn winning_and_safe_moves() {
+        // X wins in the top right corner, and blocking O's row is the only
+        // other move that doesn't hand O the win.
+        let position = after(RuleSet::Standard, &[(0, 0), (0, 1), (1, 0), (1, 1)]);
+        let squares = |moves: Vec<Move>| moves.iter().map(|m| m.coordinate).collect::<Vec<_>>();
+        assert_eq!(squares(winning_moves(&position)), vec![(2, 0)]);
+        assert_eq!(squares(safe_moves(&position)), vec![(2, 0), (2, 1)]);
+
+        // In wild games placing the X next to the O leaves a line to finish.
+        let position = after(RuleSet::Wild, &[(0, 0)]);
+        let safe = safe_moves(&position);
+        let has = |mark, coordinate| {
+            safe.contains(&Move {
+                player: 'O',
+                mark,
+                coordinate,
+            })
+        };
+        assert!(!has('X', (1, 0)));
+        assert!(has('O', (1, 0)));
+    }
+}
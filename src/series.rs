@@ -0,0 +1,230 @@
+use game::{Game, Outcome};
+use players::Player;
+use std::io::Write;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Score {
+    pub signs: [char; 2],
+    pub wins: [usize; 2],
+    pub draws: usize,
+    pub played: usize,
+}
+
+impl Score {
+    fn new(signs: [char; 2]) -> Score {
+        Score {
+            signs,
+            wins: [0, 0],
+            draws: 0,
+            played: 0,
+        }
+    }
+
+    fn record(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Won(sign) if sign == self.signs[0] => self.wins[0] += 1,
+            Outcome::Won(_) => self.wins[1] += 1,
+            Outcome::Draw => self.draws += 1,
+            Outcome::Abandoned(_) => return,
+        }
+        self.played += 1;
+    }
+
+    // True once the trailing player can't catch up in the remaining games.
+    fn is_decided(&self, games: usize) -> bool {
+        self.wins[0].abs_diff(self.wins[1]) > games - self.played
+    }
+
+    pub fn scoreboard(&self) -> String {
+        format!(
+            "Score: {} {} - {} {} ({} drawn)",
+            self.signs[0], self.wins[0], self.wins[1], self.signs[1], self.draws
+        )
+    }
+
+    pub fn summary(&self) -> String {
+        let (leader, high, low) = if self.wins[0] >= self.wins[1] {
+            (self.signs[0], self.wins[0], self.wins[1])
+        } else {
+            (self.signs[1], self.wins[1], self.wins[0])
+        };
+
+        if high == low {
+            format!("The match is tied {}-{}", high, low)
+        } else {
+            format!("Player {} wins the match {}-{}", leader, high, low)
+        }
+    }
+}
+
+// Plays up to `games` games, switching who starts after every game, and stops
+// early once the match is decided, a player leaves or doesn't want to go on.
+// `setup` configures each game, e.g. its renderer and output.
+pub fn play<F>(
+    players: [Box<dyn Player>; 2],
+    games: usize,
+    output: &mut dyn Write,
+    mut setup: F,
+) -> Score
+where
+    F: FnMut(Game) -> Game,
+{
+    let mut players = players;
+    let mut score = Score::new([players[0].sign(), players[1].sign()]);
+
+    for number in 1..=games {
+        let _ = writeln!(output, "\nGame {} of {}", number, games);
+        let swapped = number % 2 == 0;
+        if swapped {
+            players.swap(0, 1);
+        }
+
+        let game = setup(Game::new(players)).play();
+        let outcome = game.outcome();
+        players = game.into_players();
+        if swapped {
+            players.swap(0, 1);
+        }
+
+        if let Outcome::Abandoned(sign) = outcome {
+            let _ = writeln!(output, "Player {} left the match", sign);
+            break;
+        }
+        score.record(outcome);
+        let _ = writeln!(output, "{}", score.scoreboard());
+
+        if number == games || score.is_decided(games) {
+            break;
+        }
+        if !players.iter_mut().all(|player| player.play_again()) {
+            break;
+        }
+    }
+
+    let _ = writeln!(output, "{}", score.summary());
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use board::Board;
+    use game::{Observer, Transcript};
+    use players::{Action, Coordinate};
+    use robot_player::RobotPlayer;
+    use std::cell::RefCell;
+    use std::io;
+    use std::rc::Rc;
+
+    struct Quitter {
+        sign: char,
+        action: Action,
+        plays_again: bool,
+    }
+
+    impl Player for Quitter {
+        fn sign(&self) -> char {
+            self.sign
+        }
+
+        fn choose_action(&mut self, _board: Board) -> Result<Action, String> {
+            Ok(self.action.clone())
+        }
+
+        fn play_again(&mut self) -> bool {
+            self.plays_again
+        }
+    }
+
+    fn quiet(game: Game) -> Game {
+        game.with_output(Box::new(io::sink()))
+    }
+
+    fn against_robot(action: Action, plays_again: bool) -> [Box<dyn Player>; 2] {
+        [
+            Box::new(Quitter {
+                sign: 'O',
+                action,
+                plays_again,
+            }),
+            Box::new(RobotPlayer::new('X')),
+        ]
+    }
+
+    #[test]
+    fn robots_draw_every_game() {
+        let players: [Box<dyn Player>; 2] = [
+            Box::new(RobotPlayer::new('O')),
+            Box::new(RobotPlayer::new('X')),
+        ];
+        let mut output = Transcript::new();
+
+        let score = play(players, 3, &mut output, quiet);
+        assert_eq!(score.draws, 3);
+        assert_eq!(score.summary(), "The match is tied 0-0");
+        assert!(output.contents().contains("Game 3 of 3"));
+    }
+
+    #[test]
+    fn stops_once_the_match_is_decided() {
+        let mut output = Transcript::new();
+
+        let score = play(against_robot(Action::Resign, true), 3, &mut output, quiet);
+        assert_eq!(score.wins, [0, 2]);
+        assert_eq!(score.played, 2);
+        assert!(output
+            .contents()
+            .ends_with("Score: O 0 - 2 X (0 drawn)\nPlayer X wins the match 2-0\n"));
+    }
+
+    struct FirstMove {
+        signs: Rc<RefCell<Vec<char>>>,
+        seen: bool,
+    }
+
+    impl Observer for FirstMove {
+        fn on_move(&mut self, sign: char, _coordinate: Coordinate, _board: Board) {
+            if !self.seen {
+                self.signs.borrow_mut().push(sign);
+                self.seen = true;
+            }
+        }
+    }
+
+    #[test]
+    fn alternates_the_first_player() {
+        let players: [Box<dyn Player>; 2] = [
+            Box::new(RobotPlayer::new('O')),
+            Box::new(RobotPlayer::new('X')),
+        ];
+        let signs = Rc::new(RefCell::new(Vec::new()));
+
+        play(players, 3, &mut io::sink(), |game| {
+            quiet(game).with_observer(Box::new(FirstMove {
+                signs: Rc::clone(&signs),
+                seen: false,
+            }))
+        });
+        assert_eq!(*signs.borrow(), vec!['O', 'X', 'O']);
+    }
+
+    #[test]
+    fn stops_when_a_player_has_had_enough() {
+        let score = play(
+            against_robot(Action::Resign, false),
+            5,
+            &mut io::sink(),
+            quiet,
+        );
+        assert_eq!(score.played, 1);
+    }
+
+    #[test]
+    fn leaving_ends_the_match() {
+        let mut output = Transcript::new();
+
+        let score = play(against_robot(Action::Abandon, true), 3, &mut output, quiet);
+        assert_eq!(score.played, 0);
+        assert!(output.contents().contains("Player O left the match"));
+    }
+}
@@ -0,0 +1,50 @@
+use api::Api;
+use http::{read_request, write_response, Response};
+use live::Live;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use websocket;
+
+pub fn run(listener: TcpListener) {
+    let api = Arc::new(Mutex::new(Api::new()));
+    let live = Arc::new(Live::new());
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let api = Arc::clone(&api);
+                let live = Arc::clone(&live);
+                thread::spawn(move || handle_connection(stream, &api, &live));
+            }
+            Err(e) => eprintln!("Failed to accept a connection: {}", e),
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, api: &Mutex<Api>, live: &Live) {
+    let mut reader = match stream.try_clone() {
+        Ok(stream) => BufReader::new(stream),
+        Err(_) => return,
+    };
+    let mut writer = stream;
+
+    let response = match read_request(&mut reader) {
+        Ok(ref request) if websocket::is_upgrade(request) => {
+            if let Err(e) = live.join(request, reader) {
+                eprintln!("Failed to start a live connection: {}", e);
+            }
+            return;
+        }
+        Ok(request) => match api.lock() {
+            Ok(mut api) => api.handle(&request),
+            Err(_) => Response::error(500, "The server is in a bad state"),
+        },
+        Err(e) => Response::error(400, &e.to_string()),
+    };
+
+    if let Err(e) = write_response(&mut writer, &response) {
+        eprintln!("Failed to send a response: {}", e);
+    }
+}
@@ -0,0 +1,419 @@
+use game::{Observer, Outcome};
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Statistics are kept in a tab separated file with two kinds of lines:
+//
+//     profile  <name>  <streak>  <best streak>
+//     record   <name>  <opponent>  <wins>  <losses>  <draws>
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameResult {
+    Win,
+    Loss,
+    Draw,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Record {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl Record {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Profile {
+    // Positive for a run of wins, negative for a run of losses.
+    pub streak: i32,
+    pub best_streak: u32,
+    pub opponents: BTreeMap<String, Record>,
+}
+
+impl Profile {
+    pub fn total(&self) -> Record {
+        self.opponents
+            .values()
+            .fold(Record::default(), |total, record| Record {
+                wins: total.wins + record.wins,
+                losses: total.losses + record.losses,
+                draws: total.draws + record.draws,
+            })
+    }
+
+    fn record(&mut self, opponent: &str, result: GameResult) {
+        let record = self.opponents.entry(opponent.to_string()).or_default();
+        match result {
+            GameResult::Win => {
+                record.wins += 1;
+                self.streak = self.streak.max(0) + 1;
+                self.best_streak = self.best_streak.max(self.streak as u32);
+            }
+            GameResult::Loss => {
+                record.losses += 1;
+                self.streak = self.streak.min(0) - 1;
+            }
+            GameResult::Draw => {
+                record.draws += 1;
+                self.streak = 0;
+            }
+        }
+    }
+
+    fn report(&self, name: &str) -> String {
+        let total = self.total();
+        let streak = match self.streak {
+            0 => "none".to_string(),
+            1 => "1 win".to_string(),
+            -1 => "1 loss".to_string(),
+            n if n > 0 => format!("{} wins", n),
+            n => format!("{} losses", -n),
+        };
+
+        let mut lines = vec![
+            format!("Statistics for {}", name),
+            format!(
+                "  Games: {} ({} won, {} lost, {} drawn)",
+                total.games(),
+                total.wins,
+                total.losses,
+                total.draws
+            ),
+            format!("  Current streak: {}", streak),
+            format!("  Longest winning streak: {}", self.best_streak),
+        ];
+        for (opponent, record) in self.opponents.iter() {
+            lines.push(format!(
+                "  Against {}: {} won, {} lost, {} drawn",
+                opponent, record.wins, record.losses, record.draws
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Stats {
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Stats {
+    // A missing file just means no games have been recorded yet.
+    pub fn load(path: &Path) -> io::Result<Stats> {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                Stats::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(Stats::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_text())
+    }
+
+    pub fn parse(text: &str) -> Result<Stats, String> {
+        let mut stats = Stats::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let fields = line.split('\t').collect::<Vec<&str>>();
+            let invalid = || format!("Line {} of the statistics is invalid", number + 1);
+            let number_at = |i: usize| fields[i].parse::<u32>().map_err(|_| invalid());
+
+            match fields[0] {
+                "profile" if fields.len() == 4 => {
+                    let profile = stats.profiles.entry(fields[1].to_string()).or_default();
+                    profile.streak = fields[2].parse::<i32>().map_err(|_| invalid())?;
+                    profile.best_streak = number_at(3)?;
+                }
+                "record" if fields.len() == 6 => {
+                    let record = Record {
+                        wins: number_at(3)?,
+                        losses: number_at(4)?,
+                        draws: number_at(5)?,
+                    };
+                    stats
+                        .profiles
+                        .entry(fields[1].to_string())
+                        .or_default()
+                        .opponents
+                        .insert(fields[2].to_string(), record);
+                }
+                "" => {}
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(stats)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (name, profile) in self.profiles.iter() {
+            text.push_str(&format!(
+                "profile\t{}\t{}\t{}\n",
+                name, profile.streak, profile.best_streak
+            ));
+            for (opponent, record) in profile.opponents.iter() {
+                text.push_str(&format!(
+                    "record\t{}\t{}\t{}\t{}\t{}\n",
+                    name, opponent, record.wins, record.losses, record.draws
+                ));
+            }
+        }
+        text
+    }
+
+    pub fn record(&mut self, name: &str, opponent: &str, result: GameResult) {
+        self.profiles
+            .entry(name.to_string())
+            .or_default()
+            .record(opponent, result);
+    }
+
+    // Resets one profile, or all of them without a name. Returns whether
+    // there was anything to reset.
+    pub fn reset(&mut self, name: Option<&str>) -> bool {
+        match name {
+            Some(name) => self.profiles.remove(name).is_some(),
+            None => {
+                let had_profiles = !self.profiles.is_empty();
+                self.profiles.clear();
+                had_profiles
+            }
+        }
+    }
+
+    pub fn report(&self, name: Option<&str>) -> String {
+        let reports = self
+            .profiles
+            .iter()
+            .filter(|&(profile_name, _)| name.is_none_or(|name| name == profile_name))
+            .map(|(profile_name, profile)| profile.report(profile_name))
+            .collect::<Vec<String>>();
+
+        if reports.is_empty() {
+            "No games have been recorded yet".to_string()
+        } else {
+            reports.join("\n\n")
+        }
+    }
+}
+
+// Profile names end up in a tab separated file.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['\t', '\n', '\r'])
+}
+
+pub fn default_name() -> String {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .ok()
+        .filter(|name| is_valid_name(name))
+        .unwrap_or_else(|| "player".to_string())
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    data_dir(env::consts::OS, |name| env::var_os(name))
+        .map(|dir| dir.join("tic_tac_toe").join("stats.tsv"))
+}
+
+// Where the platform wants applications to keep their data.
+pub fn data_dir<F: Fn(&str) -> Option<OsString>>(os: &str, var: F) -> Option<PathBuf> {
+    let non_empty = |name: &str| var(name).filter(|value| !value.is_empty());
+    let home = non_empty("HOME").map(PathBuf::from);
+
+    match os {
+        "windows" => non_empty("APPDATA").map(PathBuf::from),
+        "macos" => home.map(|home| home.join("Library").join("Application Support")),
+        _ => non_empty("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| home.map(|home| home.join(".local").join("share"))),
+    }
+}
+
+// Records the result of every game for one player.
+#[derive(Clone)]
+pub struct StatsObserver {
+    pub path: PathBuf,
+    pub name: String,
+    pub sign: char,
+    pub opponent: String,
+}
+
+impl Observer for StatsObserver {
+    fn on_outcome(&mut self, outcome: Outcome) {
+        let result = match outcome {
+            Outcome::Won(sign) if sign == self.sign => GameResult::Win,
+            Outcome::Won(_) => GameResult::Loss,
+            Outcome::Draw => GameResult::Draw,
+            // Leaving counts as a loss, the opponent leaving doesn't count.
+            Outcome::Abandoned(sign) if sign == self.sign => GameResult::Loss,
+            Outcome::Abandoned(_) => return,
+        };
+
+        let updated = Stats::load(&self.path).and_then(|mut stats| {
+            stats.record(&self.name, &self.opponent, result);
+            stats.save(&self.path)
+        });
+        if let Err(e) = updated {
+            eprintln!(
+                "Could not update the statistics in {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streaks_follow_results() {
+        let mut stats = Stats::default();
+        for result in [
+            GameResult::Win,
+            GameResult::Win,
+            GameResult::Loss,
+            GameResult::Win,
+            GameResult::Loss,
+            GameResult::Loss,
+        ] {
+            stats.record("ada", "robot-hard", result);
+        }
+
+        let profile = &stats.profiles["ada"];
+        assert_eq!(profile.streak, -2);
+        assert_eq!(profile.best_streak, 2);
+        assert_eq!(
+            profile.total(),
+            Record {
+                wins: 3,
+                losses: 3,
+                draws: 0
+            }
+        );
+
+        stats.record("ada", "robot-easy", GameResult::Draw);
+        assert_eq!(stats.profiles["ada"].streak, 0);
+        assert_eq!(stats.profiles["ada"].total().games(), 7);
+    }
+
+    #[test]
+    fn text_round_trips() {
+        let mut stats = Stats::default();
+        stats.record("ada", "robot-hard", GameResult::Draw);
+        stats.record("ada", "network", GameResult::Win);
+        stats.record("grace", "robot-easy", GameResult::Loss);
+
+        assert_eq!(Stats::parse(&stats.to_text()), Ok(stats));
+    }
+
+    #[test]
+    fn invalid_lines_are_rejected() {
+        assert_eq!(
+            Stats::parse("profile\tada\t1\t1\nrecord\tada\trobot\tmany\t0\t0\n"),
+            Err("Line 2 of the statistics is invalid".to_string())
+        );
+    }
+
+    #[test]
+    fn reset_removes_profiles() {
+        let mut stats = Stats::default();
+        stats.record("ada", "network", GameResult::Win);
+        stats.record("grace", "network", GameResult::Win);
+
+        assert!(stats.reset(Some("ada")));
+        assert!(!stats.reset(Some("ada")));
+        assert!(stats.profiles.contains_key("grace"));
+        assert!(stats.reset(None));
+        assert_eq!(stats.report(None), "No games have been recorded yet");
+    }
+
+    #[test]
+    fn report_lists_opponents() {
+        let mut stats = Stats::default();
+        stats.record("ada", "robot-hard", GameResult::Win);
+        stats.record("ada", "robot-easy", GameResult::Loss);
+
+        assert_eq!(
+            stats.report(Some("ada")),
+            "Statistics for ada
+  Games: 2 (1 won, 1 lost, 0 drawn)
+  Current streak: 1 loss
+  Longest winning streak: 1
+  Against robot-easy: 0 won, 1 lost, 0 drawn
+  Against robot-hard: 1 won, 0 lost, 0 drawn"
+        );
+    }
+
+    #[test]
+    fn data_dir_follows_platform_conventions() {
+        let vars = |name: &str| match name {
+            "HOME" => Some(OsString::from("/home/ada")),
+            "APPDATA" => Some(OsString::from("C:\\Users\\ada\\AppData\\Roaming")),
+            _ => None,
+        };
+        assert_eq!(
+            data_dir("linux", vars),
+            Some(PathBuf::from("/home/ada/.local/share"))
+        );
+        assert_eq!(
+            data_dir("macos", vars),
+            Some(PathBuf::from("/home/ada/Library/Application Support"))
+        );
+        assert_eq!(
+            data_dir("windows", vars),
+            Some(PathBuf::from("C:\\Users\\ada\\AppData\\Roaming"))
+        );
+
+        let xdg = |name: &str| match name {
+            "XDG_DATA_HOME" => Some(OsString::from("/data")),
+            _ => None,
+        };
+        assert_eq!(data_dir("linux", xdg), Some(PathBuf::from("/data")));
+        assert_eq!(data_dir("linux", |_| None), None);
+    }
+
+    #[test]
+    fn observer_records_outcomes() {
+        let path = env::temp_dir().join(format!("tic_tac_toe_stats_{}.tsv", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut observer = StatsObserver {
+            path: path.clone(),
+            name: "ada".to_string(),
+            sign: 'O',
+            opponent: "robot-hard".to_string(),
+        };
+
+        observer.on_outcome(Outcome::Won('O'));
+        observer.on_outcome(Outcome::Abandoned('X'));
+        observer.on_outcome(Outcome::Won('X'));
+
+        let stats = Stats::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            stats.profiles["ada"].opponents["robot-hard"],
+            Record {
+                wins: 1,
+                losses: 1,
+                draws: 0
+            }
+        );
+    }
+}
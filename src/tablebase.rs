@@ -0,0 +1,325 @@
+use board::Board;
+use players::Coordinate;
+use rules::{Position, Rules, Status, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::OnceLock;
+
+// Every position of standard tic-tac-toe, solved ahead of time. The table is
+// written by `cargo run --bin tablebase`, which has to be run again whenever
+// the rules change; a test checks that it still matches them.
+const TABLE: &str = include_str!("tablebase.txt");
+
+const HEADER: &str = "\
+# Every position of standard tic-tac-toe up to turning and flipping the
+# board, as if X had started. Each line has the cells in reading order, the
+# value for the player to move (W win, L loss or D draw, with the number of
+# moves until the end) and the best moves as cell numbers from 1 to 9.
+";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub value: Value,
+    // Empty once the game is over.
+    pub moves: Vec<Coordinate>,
+}
+
+// The value and best moves for the player to move, or None for boards that
+// can't come up in a game.
+pub fn lookup(board: Board, to_move: char) -> Option<Entry> {
+    let (key, symmetry) = canonical(board, to_move)?;
+    let entry = table().get(&key)?;
+    Some(Entry {
+        value: entry.value,
+        moves: entry
+            .moves
+            .iter()
+            .map(|&square| untransform(symmetry, square))
+            .collect(),
+    })
+}
+
+// Every position that can come up when X starts, finished games included.
+pub fn reachable() -> Vec<Position> {
+    reachable_from(&Position::new(Board::new(), 'X'))
+}
+
+// The position itself and every one that can follow it.
+pub fn reachable_from(start: &Position) -> Vec<Position> {
+    let mut seen = HashSet::new();
+    let mut positions = vec![Position::new(start.board, start.to_move)];
+    seen.insert(start.board.to_cells());
+
+    let mut next = 0;
+    while next < positions.len() {
+        let position = positions[next].clone();
+        for chosen in position.legal_moves() {
+            let child = position.play(chosen).expect("legal moves can be played");
+            if seen.insert(child.board.to_cells()) {
+                positions.push(Position::new(child.board, child.to_move));
+            }
+        }
+        next += 1;
+    }
+    positions
+}
+
+// Solves the game from the rules and writes the table out.
+pub fn generate() -> String {
+    let mut table = BTreeMap::new();
+    solve(Board::new(), &mut table);
+
+    let mut text = HEADER.to_string();
+    for (cells, entry) in table {
+        let moves = if entry.moves.is_empty() {
+            "-".to_string()
+        } else {
+            entry
+                .moves
+                .iter()
+                .map(|&(x, y)| (y * 3 + x + 1).to_string())
+                .collect()
+        };
+        text.push_str(&format!("{} {} {}\n", cells, code(entry.value), moves));
+    }
+    text
+}
+
+fn table() -> &'static HashMap<String, Entry> {
+    static ENTRIES: OnceLock<HashMap<String, Entry>> = OnceLock::new();
+    ENTRIES.get_or_init(|| parse(TABLE).expect("the tablebase is well formed"))
+}
+
+fn parse(text: &str) -> Result<HashMap<String, Entry>, String> {
+    let mut entries = HashMap::new();
+    for line in text.lines().filter(|line| !line.starts_with('#')) {
+        let fields = line.split(' ').collect::<Vec<&str>>();
+        let invalid = || format!("Invalid tablebase line '{}'", line);
+        if fields.len() != 3 || fields[0].len() != 9 {
+            return Err(invalid());
+        }
+        let value = from_code(fields[1]).ok_or_else(invalid)?;
+        let moves = match fields[2] {
+            "-" => Vec::new(),
+            cells => cells
+                .chars()
+                .map(|cell| match cell.to_digit(10) {
+                    Some(n @ 1..=9) => Ok(((n as usize - 1) % 3, (n as usize - 1) / 3)),
+                    _ => Err(invalid()),
+                })
+                .collect::<Result<Vec<Coordinate>, String>>()?,
+        };
+        entries.insert(fields[0].to_string(), Entry { value, moves });
+    }
+    Ok(entries)
+}
+
+// Works on canonical boards only, so the moves it stores fit the board in
+// the table.
+fn solve(board: Board, table: &mut BTreeMap<String, Entry>) -> Value {
+    let key = board.to_cells();
+    if let Some(entry) = table.get(&key) {
+        return entry.value;
+    }
+
+    let position = Position::new(board, to_move(board));
+    let entry = match position.status() {
+        Status::Won(_) => Entry {
+            value: Value::Loss(0),
+            moves: Vec::new(),
+        },
+        Status::Draw => Entry {
+            value: Value::Draw,
+            moves: Vec::new(),
+        },
+        Status::Ongoing => {
+            let scored = position
+                .legal_moves()
+                .into_iter()
+                .map(|chosen| {
+                    let next = position.play(chosen).expect("legal moves can be played");
+                    let (child, _) = turned(next.board);
+                    (chosen.coordinate, solve(child, table).before())
+                })
+                .collect::<Vec<(Coordinate, Value)>>();
+            let value = scored
+                .iter()
+                .map(|&(_, value)| value)
+                .max_by_key(|value| value.rank())
+                .expect("an ongoing game has moves");
+            Entry {
+                value,
+                moves: scored
+                    .into_iter()
+                    .filter(|&(_, v)| v == value)
+                    .map(|(square, _)| square)
+                    .collect(),
+            }
+        }
+    };
+
+    let value = entry.value;
+    table.insert(key, entry);
+    value
+}
+
+fn code(value: Value) -> String {
+    match value {
+        Value::Win(moves) => format!("W{}", moves),
+        Value::Loss(moves) => format!("L{}", moves),
+        Value::Draw => "D".to_string(),
+    }
+}
+
+fn from_code(code: &str) -> Option<Value> {
+    let moves = || code[1..].parse::<u32>().ok();
+    match code.chars().next()? {
+        'W' => moves().map(Value::Win),
+        'L' => moves().map(Value::Loss),
+        'D' if code.len() == 1 => Some(Value::Draw),
+        _ => None,
+    }
+}
+
+// With X starting, X moves whenever both have as many marks.
+fn to_move(board: Board) -> char {
+    if count(board, 'X') == count(board, 'O') {
+        'X'
+    } else {
+        'O'
+    }
+}
+
+fn count(board: Board, sign: char) -> usize {
+    board.to_cells().chars().filter(|&c| c == sign).count()
+}
+
+// The table key for a board, and the symmetry that turns the board into it.
+fn canonical(board: Board, to_move: char) -> Option<(String, usize)> {
+    let opponent = match to_move {
+        'X' => 'O',
+        'O' => 'X',
+        _ => return None,
+    };
+    if board
+        .to_cells()
+        .chars()
+        .any(|c| c != 'X' && c != 'O' && c != '-')
+    {
+        return None;
+    }
+
+    // Swap the marks when O started, so the player to move is X exactly
+    // when both have as many marks.
+    let board = match count(board, to_move) {
+        n if n == count(board, opponent) && to_move == 'X' => board,
+        n if n + 1 == count(board, opponent) && to_move == 'O' => board,
+        n if n == count(board, opponent) || n + 1 == count(board, opponent) => swapped(board),
+        _ => return None,
+    };
+    let (board, symmetry) = turned(board);
+    Some((board.to_cells(), symmetry))
+}
+
+fn swapped(board: Board) -> Board {
+    let mut grid = board.grid;
+    for cell in grid.iter_mut().flat_map(|row| row.iter_mut()) {
+        *cell = match *cell {
+            'X' => 'O',
+            'O' => 'X',
+            other => other,
+        };
+    }
+    Board { grid }
+}
+
+// The smallest of the eight ways to turn and flip the board.
+fn turned(board: Board) -> (Board, usize) {
+    (0..8)
+        .map(|symmetry| (apply(symmetry, board), symmetry))
+        .min_by_key(|(turned, _)| turned.to_cells())
+        .expect("there are eight symmetries")
+}
+
+fn apply(symmetry: usize, board: Board) -> Board {
+    let mut turned = Board::new();
+    for y in 0..3 {
+        for x in 0..3 {
+            let (tx, ty) = transform(symmetry, (x, y));
+            turned.grid[ty][tx] = board.grid[y][x];
+        }
+    }
+    turned
+}
+
+// Flips the board for symmetries 4 to 7, then turns it a quarter at a time.
+fn transform(symmetry: usize, (x, y): Coordinate) -> Coordinate {
+    let flipped = if symmetry >= 4 { (2 - x, y) } else { (x, y) };
+    (0..symmetry % 4).fold(flipped, |(x, y), _| (2 - y, x))
+}
+
+fn untransform(symmetry: usize, square: Coordinate) -> Coordinate {
+    (0..9)
+        .map(|i| (i % 3, i / 3))
+        .find(|&original| transform(symmetry, original) == square)
+        .expect("symmetries map the board onto itself")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(rows: [&str; 3]) -> Board {
+        Board::from_cells(&rows.concat()).unwrap()
+    }
+
+    #[test]
+    fn embedded_table_matches_the_rules() {
+        assert!(TABLE == generate(), "run `cargo run --bin tablebase`");
+        assert_eq!(table().len(), 765);
+    }
+
+    #[test]
+    fn counts_every_position() {
+        let positions = reachable();
+        assert_eq!(positions.len(), 5478);
+        assert_eq!(positions.iter().filter(|p| p.is_over()).count(), 958);
+    }
+
+    #[test]
+    fn empty_board_is_a_draw_from_any_square() {
+        let entry = lookup(Board::new(), 'X').unwrap();
+        assert_eq!(entry.value, Value::Draw);
+        assert_eq!(entry.moves.len(), 9);
+        assert_eq!(lookup(Board::new(), 'O'), Some(entry));
+    }
+
+    #[test]
+    fn finds_wins_in_any_orientation_and_for_either_starter() {
+        let entry = lookup(board(["XX-", "OO-", "---"]), 'X').unwrap();
+        assert_eq!(entry.value, Value::Win(1));
+        assert_eq!(entry.moves, vec![(2, 0)]);
+
+        // The same position turned over, with O having started.
+        let entry = lookup(board(["---", "XX-", "OO-"]), 'O').unwrap();
+        assert_eq!(entry.value, Value::Win(1));
+        assert_eq!(entry.moves, vec![(2, 2)]);
+
+        let entry = lookup(board(["XXX", "OO-", "---"]), 'O').unwrap();
+        assert_eq!(entry.value, Value::Loss(0));
+        assert!(entry.moves.is_empty());
+    }
+
+    #[test]
+    fn rejects_impossible_boards() {
+        assert_eq!(lookup(board(["XXX", "---", "---"]), 'O'), None);
+        assert_eq!(lookup(board(["X--", "---", "---"]), 'X'), None);
+        assert_eq!(lookup(Board::new(), '-'), None);
+    }
+
+    #[test]
+    fn parse_reports_bad_lines() {
+        assert!(parse("X-------- Q -\n").is_err());
+        assert!(parse("X-------- D 0\n").is_err());
+        assert_eq!(parse(HEADER).map(|entries| entries.len()), Ok(0));
+    }
+}
@@ -0,0 +1,515 @@
+use board::Board;
+use game::{is_full_board, Game, Observer};
+use players::{Action, Coordinate, Player};
+use robot_player::RobotPlayer;
+use stats::StatsObserver;
+use std::cell::RefCell;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+use win_condition::winning_line;
+
+const RESET: &str = "\x1b[0m";
+const REVERSE: &str = "\x1b[7m";
+const BOLD: &str = "\x1b[1m";
+const GREEN: &str = "\x1b[32m";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    Quit,
+    Char(char),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Score {
+    pub human: u32,
+    pub robot: u32,
+    pub draws: u32,
+}
+
+pub struct Screen {
+    pub board: Board,
+    pub cursor: Coordinate,
+    pub last_move: Option<Coordinate>,
+    pub human: char,
+    pub turn: Option<char>,
+    pub result: Option<String>,
+    pub message: Option<String>,
+    pub score: Score,
+}
+
+impl Screen {
+    pub fn new(human: char) -> Screen {
+        Screen {
+            board: Board::new(),
+            cursor: (1, 1),
+            last_move: None,
+            human,
+            turn: None,
+            result: None,
+            message: None,
+            score: Score::default(),
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Screen {
+            score: self.score,
+            ..Screen::new(self.human)
+        };
+    }
+}
+
+pub struct Tui {
+    screen: Screen,
+    input: BufReader<Box<dyn Read>>,
+    output: Box<dyn Write>,
+    has_quit: bool,
+}
+
+impl Tui {
+    pub fn new(human: char, input: Box<dyn Read>, output: Box<dyn Write>) -> Tui {
+        Tui {
+            screen: Screen::new(human),
+            input: BufReader::new(input),
+            output,
+            has_quit: false,
+        }
+    }
+
+    fn draw(&mut self) -> io::Result<()> {
+        let frame = render(&self.screen);
+        self.output.write_all(frame.as_bytes())?;
+        self.output.flush()
+    }
+
+    fn read_key(&mut self) -> io::Result<Key> {
+        let key = read_key(&mut self.input)?;
+        self.has_quit |= key == Key::Quit;
+        Ok(key)
+    }
+}
+
+pub struct TuiPlayer {
+    sign: char,
+    tui: Rc<RefCell<Tui>>,
+}
+
+impl Player for TuiPlayer {
+    fn sign(&self) -> char {
+        self.sign
+    }
+
+    fn choose_action(&mut self, board: Board) -> Result<Action, String> {
+        let mut tui = self.tui.borrow_mut();
+        tui.screen.board = board;
+        tui.screen.turn = Some(self.sign);
+
+        loop {
+            if tui.draw().is_err() {
+                return Ok(Action::Abandon);
+            }
+
+            let key = match tui.read_key() {
+                Ok(key) => key,
+                Err(_) => return Ok(Action::Abandon),
+            };
+
+            match key {
+                Key::Quit => return Ok(Action::Abandon),
+                Key::Select => {
+                    let (x, y) = tui.screen.cursor;
+                    if board.grid[y][x] == '-' {
+                        tui.screen.message = None;
+                        return Ok(Action::Place((x, y)));
+                    }
+                    tui.screen.message = Some("That field is already taken".to_string());
+                }
+                key => tui.screen.cursor = move_cursor(tui.screen.cursor, key),
+            }
+        }
+    }
+}
+
+struct TuiObserver {
+    tui: Rc<RefCell<Tui>>,
+}
+
+impl Observer for TuiObserver {
+    fn on_move(&mut self, _sign: char, coordinate: Coordinate, board: Board) {
+        let mut tui = self.tui.borrow_mut();
+        tui.screen.board = board;
+        tui.screen.last_move = Some(coordinate);
+        tui.screen.turn = None;
+        let _ = tui.draw();
+    }
+
+    fn on_game_over(&mut self, board: Board, message: &str) {
+        let mut tui = self.tui.borrow_mut();
+        let human = tui.screen.human;
+        let score = &mut tui.screen.score;
+
+        match winning_line(board).map(|line| board.grid[line[0].1][line[0].0]) {
+            Some(sign) if sign == human => score.human += 1,
+            Some(_) => score.robot += 1,
+            None if is_full_board(board) => score.draws += 1,
+            None => {}
+        }
+
+        tui.screen.board = board;
+        tui.screen.turn = None;
+        tui.screen.result = Some(message.to_string());
+        let _ = tui.draw();
+    }
+}
+
+// Every game is added to the statistics when `stats` is given.
+pub fn run(stats: Option<StatsObserver>) -> io::Result<()> {
+    let _raw_mode = RawMode::enable()?;
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b[?1049h\x1b[?25l")?;
+
+    let tui = Rc::new(RefCell::new(Tui::new(
+        'O',
+        Box::new(io::stdin()),
+        Box::new(io::stdout()),
+    )));
+    let result = play_games(&tui, stats);
+
+    write!(stdout, "\x1b[?25h\x1b[?1049l")?;
+    stdout.flush()?;
+    result
+}
+
+fn play_games(tui: &Rc<RefCell<Tui>>, stats: Option<StatsObserver>) -> io::Result<()> {
+    let mut robot_starts = false;
+
+    loop {
+        tui.borrow_mut().screen.reset();
+
+        let human: Box<dyn Player> = Box::new(TuiPlayer {
+            sign: 'O',
+            tui: Rc::clone(tui),
+        });
+        let robot: Box<dyn Player> = Box::new(RobotPlayer::new('X'));
+        let players = if robot_starts {
+            [robot, human]
+        } else {
+            [human, robot]
+        };
+
+        let game = Game::new(players)
+            .with_observer(Box::new(TuiObserver {
+                tui: Rc::clone(tui),
+            }))
+            .with_output(Box::new(io::sink()));
+        match stats {
+            Some(ref stats) => game.with_observer(Box::new(stats.clone())),
+            None => game,
+        }
+        .play();
+
+        let mut tui = tui.borrow_mut();
+        loop {
+            if tui.has_quit {
+                return Ok(());
+            }
+            match tui.read_key()? {
+                Key::Char('n') | Key::Select => break,
+                _ => {}
+            }
+        }
+        robot_starts = !robot_starts;
+    }
+}
+
+pub fn render(screen: &Screen) -> String {
+    let highlighted = winning_line(screen.board).map_or(Vec::new(), |line| line.to_vec());
+    let mut frame = String::from("\x1b[H\x1b[2J");
+
+    frame.push_str("  Tic-tac-toe\r\n\r\n");
+    for y in 0..3 {
+        frame.push_str("   ");
+        for x in 0..3 {
+            let value = match screen.board.grid[y][x] {
+                '-' => ' ',
+                sign => sign,
+            };
+            let mut style = String::new();
+            if highlighted.contains(&(x, y)) {
+                style.push_str(GREEN);
+            }
+            if screen.last_move == Some((x, y)) {
+                style.push_str(BOLD);
+            }
+            if screen.result.is_none() && screen.cursor == (x, y) {
+                style.push_str(REVERSE);
+            }
+
+            if style.is_empty() {
+                frame.push_str(&format!(" {} ", value));
+            } else {
+                frame.push_str(&format!("{} {} {}", style, value, RESET));
+            }
+            if x < 2 {
+                frame.push('|');
+            }
+        }
+        frame.push_str("\r\n");
+        if y < 2 {
+            frame.push_str("   ---+---+---\r\n");
+        }
+    }
+
+    let status = match (&screen.result, screen.turn) {
+        (Some(result), _) => format!("{}  n: new game, q: quit", result),
+        (None, Some(sign)) if sign == screen.human => format!("Your turn ({})", sign),
+        (None, Some(sign)) => format!("Robot's turn ({})", sign),
+        (None, None) => "Robot's turn".to_string(),
+    };
+    frame.push_str(&format!(
+        "\r\n{} {}   You {} - {} Robot   Draws {} {}\r\n",
+        REVERSE, status, screen.score.human, screen.score.robot, screen.score.draws, RESET
+    ));
+
+    if let Some(ref message) = screen.message {
+        frame.push_str(&format!("{}\r\n", message));
+    }
+    frame.push_str("arrows/hjkl: move  enter/space: place  q: quit\r\n");
+    frame
+}
+
+pub fn read_key<R: Read>(input: &mut BufReader<R>) -> io::Result<Key> {
+    let byte = match read_byte(input)? {
+        Some(byte) => byte,
+        None => return Ok(Key::Quit),
+    };
+
+    Ok(match byte {
+        // The terminal sends an arrow key all at once, so only what has
+        // already been read belongs to it and a lone ESC doesn't wait for
+        // more.
+        0x1b => {
+            let key = match input.buffer() {
+                [b'[', b'A', ..] => Key::Up,
+                [b'[', b'B', ..] => Key::Down,
+                [b'[', b'C', ..] => Key::Right,
+                [b'[', b'D', ..] => Key::Left,
+                _ => return Ok(Key::Char('\x1b')),
+            };
+            input.consume(2);
+            key
+        }
+        b'k' => Key::Up,
+        b'j' => Key::Down,
+        b'l' => Key::Right,
+        b'h' => Key::Left,
+        b'\r' | b'\n' | b' ' => Key::Select,
+        b'q' | 0x03 | 0x04 => Key::Quit,
+        byte => Key::Char(byte as char),
+    })
+}
+
+fn read_byte<R: Read>(input: &mut BufReader<R>) -> io::Result<Option<u8>> {
+    let byte = input.fill_buf()?.first().cloned();
+    if byte.is_some() {
+        input.consume(1);
+    }
+    Ok(byte)
+}
+
+pub fn move_cursor(cursor: Coordinate, key: Key) -> Coordinate {
+    let (x, y) = cursor;
+    match key {
+        Key::Up => (x, y.saturating_sub(1)),
+        Key::Down => (x, (y + 1).min(2)),
+        Key::Left => (x.saturating_sub(1), y),
+        Key::Right => ((x + 1).min(2), y),
+        _ => cursor,
+    }
+}
+
+// Puts the terminal in raw mode until dropped. `stty` keeps this free of
+// platform specific bindings.
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> io::Result<RawMode> {
+        let saved = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+        Ok(RawMode {
+            saved: saved.trim().to_string(),
+        })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("The TUI needs an interactive terminal"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn tui(input: &str) -> Rc<RefCell<Tui>> {
+        Rc::new(RefCell::new(Tui::new(
+            'O',
+            Box::new(Cursor::new(input.as_bytes().to_vec())),
+            Box::new(io::sink()),
+        )))
+    }
+
+    #[test]
+    fn reads_arrow_and_vi_keys() {
+        let mut input = BufReader::new(Cursor::new(b"\x1b[A\x1b[Dlj\rq".to_vec()));
+        let keys = (0..6)
+            .map(|_| read_key(&mut input).unwrap())
+            .collect::<Vec<Key>>();
+        assert_eq!(
+            keys,
+            vec![
+                Key::Up,
+                Key::Left,
+                Key::Right,
+                Key::Down,
+                Key::Select,
+                Key::Quit
+            ]
+        );
+    }
+
+    #[test]
+    fn end_of_input_quits() {
+        let mut input = BufReader::new(Cursor::new(Vec::new()));
+        assert_eq!(read_key(&mut input).unwrap(), Key::Quit);
+    }
+
+    // Hands out one chunk per read, like a terminal does per key press, and
+    // fails where a terminal would wait for the next key.
+    struct KeyPresses(Vec<&'static [u8]>);
+
+    impl Read for KeyPresses {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            assert!(!self.0.is_empty(), "waited for another key press");
+            let chunk = self.0.remove(0);
+            buffer[..chunk.len()].copy_from_slice(chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    #[test]
+    fn lone_escape_does_not_wait_for_more_keys() {
+        let mut input = BufReader::new(KeyPresses(vec![b"\x1b", b"\x1b[B"]));
+        assert_eq!(read_key(&mut input).unwrap(), Key::Char('\x1b'));
+        assert_eq!(read_key(&mut input).unwrap(), Key::Down);
+    }
+
+    #[test]
+    fn cursor_stays_on_board() {
+        assert_eq!(move_cursor((0, 0), Key::Up), (0, 0));
+        assert_eq!(move_cursor((2, 2), Key::Right), (2, 2));
+        assert_eq!(move_cursor((1, 1), Key::Down), (1, 2));
+    }
+
+    #[test]
+    fn player_places_mark_under_cursor() {
+        let mut player = TuiPlayer {
+            sign: 'O',
+            tui: tui("\x1b[Ck\r"),
+        };
+        match player.choose_action(Board::new()) {
+            Ok(Action::Place(coordinate)) => assert_eq!(coordinate, (2, 0)),
+            _ => panic!("Expected a move"),
+        }
+    }
+
+    #[test]
+    fn player_cannot_select_taken_field() {
+        let tui = tui("\rl\r");
+        let mut player = TuiPlayer {
+            sign: 'O',
+            tui: Rc::clone(&tui),
+        };
+        let board = Board::new().add_value((1, 1), 'X').unwrap();
+        match player.choose_action(board) {
+            Ok(Action::Place(coordinate)) => assert_eq!(coordinate, (2, 1)),
+            _ => panic!("Expected a move"),
+        }
+    }
+
+    #[test]
+    fn quitting_abandons_the_game() {
+        let mut player = TuiPlayer {
+            sign: 'O',
+            tui: tui("q"),
+        };
+        match player.choose_action(Board::new()) {
+            Ok(Action::Abandon) => {}
+            _ => panic!("Expected to abandon"),
+        }
+    }
+
+    #[test]
+    fn render_shows_turn_score_and_highlights() {
+        let mut screen = Screen::new('O');
+        screen.board = Board {
+            grid: [['O', 'O', 'O'], ['X', 'X', '-'], ['-', '-', '-']],
+        };
+        screen.last_move = Some((2, 0));
+        screen.score = Score {
+            human: 2,
+            robot: 0,
+            draws: 1,
+        };
+        screen.result = Some("Player O won!".to_string());
+
+        let frame = render(&screen);
+        assert!(frame.contains("Player O won!"));
+        assert!(frame.contains("You 2 - 0 Robot   Draws 1"));
+        assert!(frame.contains(&format!("{}{} O {}", GREEN, BOLD, RESET)));
+        assert!(!frame.contains(&format!("{} X", REVERSE)));
+    }
+
+    #[test]
+    fn render_highlights_cursor_during_turn() {
+        let mut screen = Screen::new('O');
+        screen.turn = Some('O');
+        let frame = render(&screen);
+        assert!(frame.contains("Your turn (O)"));
+        assert!(frame.contains(&format!("{}   {}", REVERSE, RESET)));
+    }
+
+    #[test]
+    fn game_over_updates_score() {
+        let tui = tui("");
+        let mut observer = TuiObserver {
+            tui: Rc::clone(&tui),
+        };
+        let board = Board {
+            grid: [['X', 'X', 'X'], ['O', 'O', '-'], ['-', '-', '-']],
+        };
+        observer.on_game_over(board, "Player X won!");
+        assert_eq!(tui.borrow().screen.score.robot, 1);
+    }
+}
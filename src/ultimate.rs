@@ -0,0 +1,326 @@
+use board::Board;
+use notation::{CellNumbers, Notation};
+use players::Coordinate;
+use robot_player::get_opponent_sign;
+use rules::{Rules, Status};
+use variant::Variant;
+use win_condition::winning_line;
+
+// Ultimate tic-tac-toe is played on a 3×3 grid of boards. The cell you play
+// in decides which board your opponent has to play in next, unless that
+// board is already won or full. Winning a board claims its square on the
+// meta board, and three claimed squares in a row win the game.
+//
+// Boards and cells are both numbered 1 to 9 in reading order, so `53` is the
+// top right cell of the center board.
+
+// The board and the cell within it.
+pub type Move = (Coordinate, Coordinate);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UltimateBoard {
+    // Indexed `[y][x]` like `Board::grid`.
+    pub boards: [[Board; 3]; 3],
+    pub meta: Board,
+    // `None` when the player may pick any open board.
+    pub next_board: Option<Coordinate>,
+    pub to_move: char,
+}
+
+impl Default for UltimateBoard {
+    fn default() -> UltimateBoard {
+        UltimateBoard::new()
+    }
+}
+
+impl UltimateBoard {
+    pub fn new() -> UltimateBoard {
+        UltimateBoard {
+            boards: [[Board::new(); 3]; 3],
+            meta: Board::new(),
+            next_board: None,
+            to_move: 'X',
+        }
+    }
+
+    pub fn board(&self, board: Coordinate) -> Board {
+        self.boards[board.1][board.0]
+    }
+
+    // Won and full boards can't be played in any more.
+    pub fn is_closed(&self, board: Coordinate) -> bool {
+        self.meta.grid[board.1][board.0] != '-'
+            || self
+                .board(board)
+                .grid
+                .iter()
+                .flatten()
+                .all(|&value| value != '-')
+    }
+
+    pub fn is_playable(&self, board: Coordinate) -> bool {
+        !self.is_closed(board) && self.next_board.is_none_or(|next| next == board)
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.is_over() {
+            return Vec::new();
+        }
+
+        let mut moves = Vec::new();
+        for board in squares() {
+            if !self.is_playable(board) {
+                continue;
+            }
+            for cell in squares() {
+                if self.board(board).grid[cell.1][cell.0] == '-' {
+                    moves.push((board, cell));
+                }
+            }
+        }
+        moves
+    }
+
+    pub fn play(&self, (board, cell): Move) -> Result<UltimateBoard, String> {
+        if self.is_over() {
+            return Err("The game is already over".to_string());
+        }
+        if board.0 > 2 || board.1 > 2 {
+            return Err("The board is out of bounds".to_string());
+        }
+        if self.is_closed(board) {
+            return Err("That board is already decided".to_string());
+        }
+        if !self.is_playable(board) {
+            return Err("You have to play in the board your opponent sent you to".to_string());
+        }
+
+        let mut next = *self;
+        let sub_board = self.board(board).add_value(cell, self.to_move)?;
+        next.boards[board.1][board.0] = sub_board;
+        if winning_line(sub_board).is_some() {
+            next.meta = next.meta.add_value(board, self.to_move)?;
+        }
+        next.next_board = if next.is_closed(cell) {
+            None
+        } else {
+            Some(cell)
+        };
+        next.to_move = get_opponent_sign(self.to_move);
+        Ok(next)
+    }
+
+    pub fn winner(&self) -> Option<char> {
+        winning_line(self.meta).map(|line| self.meta.grid[line[0].1][line[0].0])
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some() || squares().all(|board| self.is_closed(board))
+    }
+}
+
+impl Rules for UltimateBoard {
+    type Move = Move;
+
+    fn to_move(&self) -> char {
+        self.to_move
+    }
+
+    fn legal_moves(&self) -> Vec<Move> {
+        UltimateBoard::legal_moves(self)
+    }
+
+    fn play(&self, chosen: Move) -> Result<UltimateBoard, String> {
+        UltimateBoard::play(self, chosen)
+    }
+
+    fn status(&self) -> Status {
+        match UltimateBoard::winner(self) {
+            Some(sign) => Status::Won(sign),
+            None if UltimateBoard::is_over(self) => Status::Draw,
+            None => Status::Ongoing,
+        }
+    }
+}
+
+fn squares() -> impl Iterator<Item = Coordinate> {
+    (0..9).map(|i| (i % 3, i / 3))
+}
+
+// Reads `<board><cell>` like `53`, optionally separated by a space, comma or
+// dash. The board can be left out when it's forced.
+pub fn parse_move(input: &str, board: &UltimateBoard) -> Result<Move, String> {
+    let digits = input
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | ',' | '-' | '/'))
+        .map(|c| c.to_string())
+        .collect::<Vec<String>>();
+
+    match (digits.len(), board.next_board) {
+        (2, _) => Ok((
+            CellNumbers.parse(&digits[0])?,
+            CellNumbers.parse(&digits[1])?,
+        )),
+        (1, Some(next)) => Ok((next, CellNumbers.parse(&digits[0])?)),
+        (1, None) => Err("You can play in any board, so enter the board and the cell".to_string()),
+        _ => Err("Enter the board and the cell as two numbers from 1 to 9, like 53".to_string()),
+    }
+}
+
+pub fn format_move((board, cell): Move) -> String {
+    format!("{}{}", CellNumbers.format(board), CellNumbers.format(cell))
+}
+
+// Empty cells are drawn as `-` in the boards the next move can go to and as
+// `.` everywhere else.
+pub fn render(board: &UltimateBoard) -> String {
+    let mut lines = Vec::new();
+
+    for by in 0..3 {
+        if by > 0 {
+            lines.push("-------+-------+-------".to_string());
+        }
+        for y in 0..3 {
+            let row = (0..3)
+                .map(|bx| {
+                    let cells = (0..3)
+                        .map(|x| match board.boards[by][bx].grid[y][x] {
+                            '-' if board.is_playable((bx, by)) && !board.is_over() => '-',
+                            '-' => '.',
+                            value => value,
+                        })
+                        .map(|c| c.to_string())
+                        .collect::<Vec<String>>();
+                    format!(" {} ", cells.join(" "))
+                })
+                .collect::<Vec<String>>();
+            lines.push(row.join("|").trim_end().to_string());
+        }
+    }
+
+    let won = squares()
+        .filter(|&(x, y)| board.meta.grid[y][x] != '-')
+        .map(|(x, y)| format!("{} {}", CellNumbers.format((x, y)), board.meta.grid[y][x]))
+        .collect::<Vec<String>>();
+    if !won.is_empty() {
+        lines.push(format!("Won boards: {}", won.join(", ")));
+    }
+    lines.join("\n")
+}
+
+impl Variant for UltimateBoard {
+    fn prompt(&self) -> String {
+        match self.next_board {
+            Some(next) => format!(
+                "play in board {} (enter a cell from 1 to 9)",
+                CellNumbers.format(next)
+            ),
+            None => "enter a board and a cell, like 53".to_string(),
+        }
+    }
+
+    fn parse_move(&self, input: &str) -> Result<Move, String> {
+        parse_move(input, self)
+    }
+
+    fn format_move(&self, chosen: Move) -> String {
+        format_move(chosen)
+    }
+
+    fn render(&self) -> String {
+        render(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcts::{search, Budget};
+    use random::Random;
+
+    fn play_all(moves: &[&str]) -> UltimateBoard {
+        moves.iter().fold(UltimateBoard::new(), |board, input| {
+            board.play(parse_move(input, &board).unwrap()).unwrap()
+        })
+    }
+
+    #[test]
+    fn cell_sends_opponent_to_board() {
+        let board = play_all(&["53"]);
+        assert_eq!(board.next_board, Some((2, 0)));
+        assert_eq!(board.to_move, 'O');
+        assert!(board.play(((1, 1), (0, 0))).is_err());
+        assert_eq!(board.legal_moves().len(), 9);
+    }
+
+    #[test]
+    fn winning_a_board_claims_it() {
+        let board = play_all(&["51", "15", "55", "54", "42", "25"]);
+        assert_eq!(board.meta.grid[1][1], '-');
+
+        let board = board.play(((1, 1), (2, 2))).unwrap();
+        assert_eq!(board.meta.grid[1][1], 'X');
+        assert!(board.is_closed((1, 1)));
+        assert_eq!(board.next_board, Some((2, 2)));
+    }
+
+    #[test]
+    fn closed_board_frees_the_next_move() {
+        let board = play_all(&["51", "15", "55", "54", "42", "25", "59", "95"]);
+        assert_eq!(board.next_board, None);
+        assert!(board.play(((1, 1), (0, 1))).is_err());
+        assert!(board.play(((0, 0), (0, 0))).is_ok());
+    }
+
+    #[test]
+    fn three_boards_in_a_row_win() {
+        let mut board = UltimateBoard::new();
+        for &(x, y) in [(0, 0), (1, 0), (2, 0)].iter() {
+            board.meta = board.meta.add_value((x, y), 'X').unwrap();
+        }
+        assert_eq!(board.winner(), Some('X'));
+        assert!(board.is_over());
+        assert!(board.legal_moves().is_empty());
+    }
+
+    #[test]
+    fn notation_round_trips() {
+        let board = UltimateBoard::new();
+        assert_eq!(parse_move("5 3", &board), Ok(((1, 1), (2, 0))));
+        assert_eq!(parse_move("53", &board), Ok(((1, 1), (2, 0))));
+        assert_eq!(format_move(((1, 1), (2, 0))), "53");
+        assert!(parse_move("3", &board).is_err());
+        assert_eq!(parse_move("3", &play_all(&["51"])), Ok(((0, 0), (2, 0))));
+    }
+
+    #[test]
+    fn renders_playable_boards() {
+        let expected = " . . . | . . . | . . .
+ . . . | . . . | . . .
+ . . . | . . . | . . .
+-------+-------+-------
+ . . . | . . . | - - -
+ . . . | . . X | - - -
+ . . . | . . . | - - -
+-------+-------+-------
+ . . . | . . . | . . .
+ . . . | . . . | . . .
+ . . . | . . . | . . .";
+        assert_eq!(render(&play_all(&["56"])), expected);
+    }
+
+    #[test]
+    fn search_takes_the_winning_move() {
+        let mut board = UltimateBoard::new();
+        board.meta = board.meta.add_value((0, 0), 'X').unwrap();
+        board.meta = board.meta.add_value((1, 0), 'X').unwrap();
+        board.boards[0][2] = Board {
+            grid: [['X', 'X', '-'], ['O', 'O', '-'], ['-', '-', '-']],
+        };
+        board.next_board = Some((2, 0));
+
+        let chosen = search(&board, Budget::Iterations(300), &mut Random::new(1));
+        assert_eq!(chosen, Some(((2, 0), (2, 0))));
+    }
+}
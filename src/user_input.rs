@@ -1,18 +1,147 @@
 use board::Board;
-use std::io::{self, BufRead};
+use commands::{parse_command, Command, HELP};
+use game::RuleSet;
+use notation::{AutoDetect, Notation};
+use players::{Action, Player};
+use robot_player::best_coordinate;
+use std::io::{self, BufRead, Write};
+use wild::wild_move;
 
-pub fn get_coordinate_from_user(_sign: char, _board: Board) -> Result<(usize, usize), String> {
-    println!("Enter a coordinate in the format x,y:");
-    let input = read_line();
-    parse_user_input(&input)
+pub struct HumanPlayer {
+    pub sign: char,
+    notation: Box<dyn Notation>,
+    // Only used for hints.
+    rules: RuleSet,
+    // Reads from stdin when `None`.
+    input: Option<Box<dyn BufRead>>,
+    output: Box<dyn Write>,
 }
 
-fn parse_user_input(input: &str) -> Result<(usize, usize), String> {
+impl HumanPlayer {
+    pub fn new(sign: char) -> HumanPlayer {
+        HumanPlayer {
+            sign,
+            notation: Box::new(AutoDetect),
+            rules: RuleSet::Standard,
+            input: None,
+            output: Box::new(io::stdout()),
+        }
+    }
+
+    pub fn with_notation(self, notation: Box<dyn Notation>) -> Self {
+        HumanPlayer { notation, ..self }
+    }
+
+    pub fn with_rules(self, rules: RuleSet) -> Self {
+        HumanPlayer { rules, ..self }
+    }
+
+    pub fn with_input(self, input: Box<dyn BufRead>) -> Self {
+        HumanPlayer {
+            input: Some(input),
+            ..self
+        }
+    }
+
+    // Where prompts and hints go, usually the same place as the game's output.
+    pub fn with_output(self, output: Box<dyn Write>) -> Self {
+        HumanPlayer { output, ..self }
+    }
+
+    fn print(&mut self, text: &str) {
+        let _ = writeln!(self.output, "{}", text);
+    }
+
+    // Returns `None` once the input is exhausted.
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        let read = match self.input {
+            Some(ref mut input) => input.read_line(&mut line)?,
+            None => io::stdin().read_line(&mut line)?,
+        };
+        Ok(if read == 0 { None } else { Some(line) })
+    }
+
+    // Gives up on the game at the end of the input or when it can't be read,
+    // as asking again would never get an answer.
+    fn next_line(&mut self) -> Option<String> {
+        loop {
+            match self.read_line() {
+                Ok(line) => return line,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    eprintln!("Could not read the input: {}", e);
+                    return None;
+                }
+            }
+        }
+    }
+
+    // Wild games need the mark as well as the square.
+    fn hint(&self, board: Board) -> Result<String, String> {
+        if self.rules == RuleSet::Wild {
+            let (coordinate, mark) = wild_move(board)?;
+            return Ok(format!("{} {}", mark, self.notation.format(coordinate)));
+        }
+        best_coordinate(self.rules, self.sign, board).map(|c| self.notation.format(c))
+    }
+}
+
+impl Player for HumanPlayer {
+    fn sign(&self) -> char {
+        self.sign
+    }
+
+    fn choose_action(&mut self, board: Board) -> Result<Action, String> {
+        loop {
+            let prompt = self.notation.prompt();
+            self.print(prompt);
+            let line = match self.next_line() {
+                Some(line) => line,
+                None => return Ok(Action::Abandon),
+            };
+            match parse_command(&line, &*self.notation)? {
+                Command::Help => self.print(HELP),
+                Command::Hint => match self.hint(board) {
+                    Ok(hint) => self.print(&format!("Try {}", hint)),
+                    Err(e) => self.print(&e),
+                },
+                Command::Action(action) => return Ok(action),
+            }
+        }
+    }
+
+    fn accept_draw(&mut self, _board: Board) -> bool {
+        let question = format!(
+            "Player {}, your opponent offers a draw. Do you accept? (y/n)",
+            self.sign
+        );
+        self.print(&question);
+        self.next_line()
+            .is_some_and(|answer| answer.trim().to_lowercase().starts_with('y'))
+    }
+
+    fn play_again(&mut self) -> bool {
+        self.print("Play the next game? (Y/n)");
+        self.next_line().is_some_and(|answer| {
+            let answer = answer.trim().to_lowercase();
+            answer.is_empty() || answer.starts_with('y')
+        })
+    }
+}
+
+pub fn parse_user_input(input: &str) -> Result<(usize, usize), String> {
     if input.trim().is_empty() {
         return Err("The input can't be empty".to_string());
     }
 
-    let vec = input.split(',').collect::<Vec<&str>>();
+    // Also accept `(x,y)` and `x y`.
+    let input = input.trim().trim_start_matches('(').trim_end_matches(')');
+    let vec = if input.contains(',') {
+        input.split(',').collect::<Vec<&str>>()
+    } else {
+        input.split_whitespace().collect::<Vec<&str>>()
+    };
 
     if vec.len() > 2 || vec.len() == 1 {
         return Err("You have to pass two values".to_string());
@@ -21,10 +150,7 @@ fn parse_user_input(input: &str) -> Result<(usize, usize), String> {
     let x_result = vec[0].trim().parse::<usize>();
     let y_result = vec[1].trim().parse::<usize>();
 
-    if x_result.is_ok() && y_result.is_ok() {
-        let x = x_result.unwrap();
-        let y = y_result.unwrap();
-
+    if let (Ok(x), Ok(y)) = (x_result, y_result) {
         if x > 2 || y > 2 {
             return Err("Value can't be larger than 2".to_string());
         }
@@ -35,11 +161,13 @@ fn parse_user_input(input: &str) -> Result<(usize, usize), String> {
     Err("The value can't be smaller than 0".to_string())
 }
 
-fn read_line() -> String {
-    let mut line = String::new();
-    let stdin = io::stdin();
-    stdin.lock().read_line(&mut line).unwrap();
-    line
+// A column counted from 1 at the left, for the variants where marks drop
+// down the board.
+pub fn parse_column(input: &str, columns: usize) -> Result<usize, String> {
+    match input.trim().parse::<usize>() {
+        Ok(column) if (1..=columns).contains(&column) => Ok(column - 1),
+        _ => Err(format!("Enter a column from 1 to {}", columns)),
+    }
 }
 
 #[cfg(test)]
@@ -49,13 +177,13 @@ mod input_parsing_tests {
     #[test]
     fn empty_string_gives_error() {
         let result = parse_user_input("");
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
     fn whitespace_gives_error() {
         let result = parse_user_input(" ");
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
@@ -70,27 +198,107 @@ mod input_parsing_tests {
         assert_eq!(result.unwrap(), (1, 1));
     }
 
+    #[test]
+    fn space_separated_values_work() {
+        let result = parse_user_input("2 1");
+        assert_eq!(result.unwrap(), (2, 1));
+    }
+
+    #[test]
+    fn parenthesized_values_work() {
+        let result = parse_user_input("(0, 2)");
+        assert_eq!(result.unwrap(), (0, 2));
+    }
+
     #[test]
     fn too_many_values_gives_error() {
         let result = parse_user_input("1,1,1");
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
     fn negative_values_give_error() {
         let result = parse_user_input("-1,1");
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
     fn too_large_values_give_error() {
         let result = parse_user_input("3,5");
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
     fn only_one_value_give_error() {
         let result = parse_user_input("1");
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn columns_count_from_one() {
+        assert_eq!(parse_column(" 1\n", 7), Ok(0));
+        assert_eq!(parse_column("7", 7), Ok(6));
+        assert!(parse_column("0", 7).is_err());
+        assert!(parse_column("8", 7).is_err());
+        assert!(parse_column("1,1", 7).is_err());
+    }
+}
+
+#[cfg(test)]
+mod human_player_tests {
+    use super::*;
+    use game::Game;
+    use robot_player::RobotPlayer;
+    use std::io::{Cursor, Read};
+
+    struct BrokenInput;
+
+    impl Read for BrokenInput {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("the terminal went away"))
+        }
+    }
+
+    fn play_against_robot(human: HumanPlayer) -> String {
+        Game::new([Box::new(human), Box::new(RobotPlayer::new('X'))])
+            .with_output(Box::new(io::sink()))
+            .play()
+            .get_final_message()
+    }
+
+    #[test]
+    fn reads_moves_from_injected_input() {
+        let input = Cursor::new("1,1\n");
+        let mut human = HumanPlayer::new('O').with_input(Box::new(input));
+
+        let action = human.choose_action(Board::new());
+        assert_eq!(action, Ok(Action::Place((1, 1))));
+    }
+
+    #[test]
+    fn empty_line_is_an_error() {
+        let input = Cursor::new("\n");
+        let mut human = HumanPlayer::new('O').with_input(Box::new(input));
+
+        assert!(human.choose_action(Board::new()).is_err());
+    }
+
+    #[test]
+    fn end_of_input_abandons_the_game() {
+        let human = HumanPlayer::new('O').with_input(Box::new(Cursor::new("1,1\n\n")));
+        assert_eq!(play_against_robot(human), "Player O left the game");
+    }
+
+    #[test]
+    fn read_error_abandons_the_game() {
+        let input = io::BufReader::new(BrokenInput);
+        let human = HumanPlayer::new('O').with_input(Box::new(input));
+        assert_eq!(play_against_robot(human), "Player O left the game");
+    }
+
+    #[test]
+    fn end_of_input_declines_a_draw() {
+        let mut human = HumanPlayer::new('O').with_input(Box::new(Cursor::new("")));
+        assert!(!human.accept_draw(Board::new()));
     }
 }
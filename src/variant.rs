@@ -0,0 +1,253 @@
+use game::Outcome;
+use rules::{Rules, Status};
+use std::io::{self, BufRead, Write};
+
+// What the terminal game loop needs on top of the rules: how moves are read
+// and written, how the position is drawn and how the game ended.
+pub trait Variant: Rules + PartialEq {
+    // How a player is called in the middle of a sentence.
+    fn player_name(&self, sign: char) -> String {
+        format!("player {}", sign)
+    }
+
+    // What the player to move is asked to enter, like `enter a column from 1
+    // to 7`.
+    fn prompt(&self) -> String;
+
+    fn parse_move(&self, input: &str) -> Result<Self::Move, String>;
+
+    fn format_move(&self, chosen: Self::Move) -> String;
+
+    fn render(&self) -> String;
+
+    // How the game ended, once it's over.
+    fn result(&self) -> String {
+        match self.status() {
+            Status::Won(sign) => format!("{} won!", capitalized(&self.player_name(sign))),
+            _ => "It's a tie!".to_string(),
+        }
+    }
+
+    // How many times the same position may come up before the game is a
+    // draw, for games where pieces move back and forth.
+    fn repetitions(&self) -> Option<usize> {
+        None
+    }
+}
+
+pub fn capitalized(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+pub trait VariantPlayer<R: Variant> {
+    fn sign(&self) -> char;
+
+    // `Ok(None)` leaves the game, an error is shown before asking again.
+    fn choose_move(&mut self, position: &R) -> Result<Option<R::Move>, String>;
+}
+
+pub struct VariantHuman {
+    sign: char,
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+}
+
+impl VariantHuman {
+    pub fn new(sign: char, input: Box<dyn BufRead>, output: Box<dyn Write>) -> VariantHuman {
+        VariantHuman {
+            sign,
+            input,
+            output,
+        }
+    }
+}
+
+impl<R: Variant> VariantPlayer<R> for VariantHuman {
+    fn sign(&self) -> char {
+        self.sign
+    }
+
+    fn choose_move(&mut self, position: &R) -> Result<Option<R::Move>, String> {
+        let _ = writeln!(
+            self.output,
+            "{}, {}:",
+            capitalized(&position.player_name(self.sign)),
+            position.prompt()
+        );
+
+        let mut line = String::new();
+        match self.input.read_line(&mut line) {
+            Ok(0) | Err(_) => return Ok(None),
+            Ok(_) => {}
+        }
+        if line.trim() == "quit" {
+            return Ok(None);
+        }
+        position.parse_move(&line).map(Some)
+    }
+}
+
+// Plays a game from `start` to the end. The player whose sign is to move
+// chooses each move, so games where the same player can move twice in a row
+// need nothing special.
+pub fn play<R: Variant>(
+    start: R,
+    mut players: [Box<dyn VariantPlayer<R>>; 2],
+    output: &mut dyn Write,
+) -> io::Result<Outcome> {
+    let mut position = start;
+    let mut seen = vec![position.clone()];
+    writeln!(output, "{}", position.render())?;
+
+    while !position.is_over() {
+        let sign = position.to_move();
+        let player = match players.iter_mut().find(|player| player.sign() == sign) {
+            Some(player) => player,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Nobody plays {}", sign),
+                ))
+            }
+        };
+        let chosen = match player.choose_move(&position) {
+            Ok(Some(chosen)) => chosen,
+            Ok(None) => {
+                writeln!(
+                    output,
+                    "Game over\n{} left the game",
+                    capitalized(&position.player_name(sign))
+                )?;
+                return Ok(Outcome::Abandoned(sign));
+            }
+            Err(e) => {
+                writeln!(output, "{}", e)?;
+                continue;
+            }
+        };
+
+        let played = position.format_move(chosen);
+        position = match position.play(chosen) {
+            Ok(next) => next,
+            Err(e) => {
+                writeln!(output, "{}", e)?;
+                continue;
+            }
+        };
+        writeln!(
+            output,
+            "\nBoard after {}'s turn ({}):\n\n{}",
+            position.player_name(sign),
+            played,
+            position.render()
+        )?;
+
+        if let Some(limit) = position.repetitions() {
+            seen.push(position.clone());
+            if seen.iter().filter(|&earlier| *earlier == position).count() >= limit {
+                writeln!(
+                    output,
+                    "Game over\nThe same position came up {} times, it's a draw",
+                    limit
+                )?;
+                return Ok(Outcome::Draw);
+            }
+        }
+    }
+
+    writeln!(output, "Game over\n{}", position.result())?;
+    Ok(match position.status() {
+        Status::Won(sign) => Outcome::Won(sign),
+        _ => Outcome::Draw,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::Transcript;
+    use gravity::Gravity;
+    use mcts::MctsPlayer;
+    use morris::Morris;
+    use notakto::Notakto;
+    use order_chaos::OrderChaos;
+    use quantum::Quantum;
+    use qubic::Cube;
+    use std::io::Cursor;
+    use ultimate::UltimateBoard;
+
+    // A human can make `first` and then leave, and two quick robots can
+    // play a game from `start` to the end.
+    fn check_variant<R: Variant>(start: R, first: &'static str) {
+        let sign = start.to_move();
+        let chosen = start.parse_move(first).unwrap();
+        let opponent = start.play(chosen).unwrap().to_move();
+
+        let human = VariantHuman::new(
+            sign,
+            Box::new(Cursor::new(format!("{}\nquit\n", first))),
+            Box::new(io::sink()),
+        );
+        let robot = MctsPlayer::new(opponent, 10).with_seed(1);
+        let mut output = Transcript::new();
+        let outcome = play(
+            start.clone(),
+            [Box::new(human), Box::new(robot)],
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::Abandoned(sign));
+        let turn = format!(
+            "{}'s turn ({})",
+            start.player_name(sign),
+            start.format_move(chosen)
+        );
+        assert!(output.contents().contains(&turn), "{}", output.contents());
+
+        let robots: [Box<dyn VariantPlayer<R>>; 2] = [
+            Box::new(MctsPlayer::new(sign, 10).with_seed(2)),
+            Box::new(MctsPlayer::new(opponent, 10).with_seed(3)),
+        ];
+        let outcome = play(start, robots, &mut io::sink()).unwrap();
+        assert!(matches!(outcome, Outcome::Won(_) | Outcome::Draw));
+    }
+
+    #[test]
+    fn ultimate() {
+        check_variant(UltimateBoard::new(), "55");
+    }
+
+    #[test]
+    fn qubic() {
+        check_variant(Cube::new(), "1,1,1");
+    }
+
+    #[test]
+    fn notakto() {
+        check_variant(Notakto::new(2), "15");
+    }
+
+    #[test]
+    fn order_chaos() {
+        check_variant(OrderChaos::new(), "X 0,0");
+    }
+
+    #[test]
+    fn morris() {
+        check_variant(Morris::new(), "5");
+    }
+
+    #[test]
+    fn gravity() {
+        check_variant(Gravity::default(), "4");
+    }
+
+    #[test]
+    fn quantum() {
+        check_variant(Quantum::new(), "15");
+    }
+}
@@ -0,0 +1,387 @@
+use http::Request;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const MAX_MESSAGE_LENGTH: usize = 64 * 1024;
+
+const CONTINUATION: u8 = 0x0;
+const TEXT: u8 = 0x1;
+const CLOSE: u8 = 0x8;
+const PING: u8 = 0x9;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Role {
+    Server,
+    Client,
+}
+
+// The sending half can be cloned so several threads can write to the same
+// connection, e.g. to broadcast moves to players and spectators.
+pub struct Sender {
+    stream: TcpStream,
+    role: Role,
+}
+
+impl Sender {
+    pub fn send_text(&mut self, message: &str) -> io::Result<()> {
+        self.send_frame(TEXT, message.as_bytes())
+    }
+
+    pub fn close(&mut self) {
+        let _ = self.send_frame(CLOSE, &[]);
+    }
+
+    pub fn try_clone(&self) -> io::Result<Sender> {
+        Ok(Sender {
+            stream: self.stream.try_clone()?,
+            role: self.role,
+        })
+    }
+
+    fn send_frame(&mut self, opcode: u8, payload: &[u8]) -> io::Result<()> {
+        let mut frame = vec![0x80 | opcode];
+        let mask_bit = if self.role == Role::Client { 0x80 } else { 0 };
+
+        if payload.len() < 126 {
+            frame.push(mask_bit | payload.len() as u8);
+        } else if payload.len() <= 0xffff {
+            frame.push(mask_bit | 126);
+            frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        } else {
+            frame.push(mask_bit | 127);
+            frame.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+        }
+
+        if self.role == Role::Client {
+            let mask = masking_key();
+            frame.extend_from_slice(&mask);
+            frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        } else {
+            frame.extend_from_slice(payload);
+        }
+
+        self.stream.write_all(&frame)?;
+        self.stream.flush()
+    }
+}
+
+pub struct WebSocket {
+    reader: BufReader<TcpStream>,
+    sender: Sender,
+}
+
+impl WebSocket {
+    pub fn send_text(&mut self, message: &str) -> io::Result<()> {
+        self.sender.send_text(message)
+    }
+
+    pub fn sender(&self) -> io::Result<Sender> {
+        self.sender.try_clone()
+    }
+
+    pub fn close(&mut self) {
+        self.sender.close();
+    }
+
+    // Whether the other end has gone away, found out without waiting for a
+    // message.
+    pub fn is_closed(&self) -> bool {
+        if let Some(&first) = self.reader.buffer().first() {
+            return first & 0x0f == CLOSE;
+        }
+
+        let stream = self.reader.get_ref();
+        if stream.set_nonblocking(true).is_err() {
+            return true;
+        }
+        let mut first = [0; 1];
+        let closed = match stream.peek(&mut first) {
+            Ok(0) => true,
+            Ok(_) => first[0] & 0x0f == CLOSE,
+            Err(ref e) => e.kind() != io::ErrorKind::WouldBlock,
+        };
+        stream.set_nonblocking(false).is_err() || closed
+    }
+
+    // Returns `None` once the other end has closed the connection.
+    pub fn receive_text(&mut self) -> io::Result<Option<String>> {
+        let mut message = Vec::new();
+
+        loop {
+            let (fin, opcode, payload) = match self.read_frame() {
+                Ok(frame) => frame,
+                Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e),
+            };
+
+            match opcode {
+                TEXT | CONTINUATION => {
+                    message.extend_from_slice(&payload);
+                    if message.len() > MAX_MESSAGE_LENGTH {
+                        return Err(invalid_data("The message is too large"));
+                    }
+                    if fin {
+                        return String::from_utf8(message)
+                            .map(Some)
+                            .map_err(|_| invalid_data("The message isn't UTF-8"));
+                    }
+                }
+                CLOSE => {
+                    self.close();
+                    return Ok(None);
+                }
+                PING => self.sender.send_frame(0xA, &payload)?,
+                _ => {}
+            }
+        }
+    }
+
+    fn read_frame(&mut self) -> io::Result<(bool, u8, Vec<u8>)> {
+        let mut header = [0; 2];
+        self.reader.read_exact(&mut header)?;
+        let fin = header[0] & 0x80 != 0;
+        let opcode = header[0] & 0x0f;
+        let masked = header[1] & 0x80 != 0;
+
+        let length = match header[1] & 0x7f {
+            126 => {
+                let mut bytes = [0; 2];
+                self.reader.read_exact(&mut bytes)?;
+                u64::from(u16::from_be_bytes(bytes))
+            }
+            127 => {
+                let mut bytes = [0; 8];
+                self.reader.read_exact(&mut bytes)?;
+                u64::from_be_bytes(bytes)
+            }
+            length => u64::from(length),
+        };
+        if length > MAX_MESSAGE_LENGTH as u64 {
+            return Err(invalid_data("The frame is too large"));
+        }
+
+        let mut mask = [0; 4];
+        if masked {
+            self.reader.read_exact(&mut mask)?;
+        }
+
+        let mut payload = vec![0; length as usize];
+        self.reader.read_exact(&mut payload)?;
+        if masked {
+            for (i, byte) in payload.iter_mut().enumerate() {
+                *byte ^= mask[i % 4];
+            }
+        }
+        Ok((fin, opcode, payload))
+    }
+}
+
+pub fn is_upgrade(request: &Request) -> bool {
+    request.method == "GET"
+        && request
+            .header("Upgrade")
+            .is_some_and(|value| value.eq_ignore_ascii_case("websocket"))
+}
+
+// Completes the handshake for a request that has already been read from
+// `reader`, keeping the reader so no buffered frames are lost.
+pub fn accept(request: &Request, reader: BufReader<TcpStream>) -> io::Result<WebSocket> {
+    let key = request
+        .header("Sec-WebSocket-Key")
+        .ok_or_else(|| invalid_data("Missing Sec-WebSocket-Key"))?;
+
+    let mut stream = reader.get_ref().try_clone()?;
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    )?;
+
+    Ok(WebSocket {
+        reader,
+        sender: Sender {
+            stream,
+            role: Role::Server,
+        },
+    })
+}
+
+pub fn connect(address: &str, path: &str) -> io::Result<WebSocket> {
+    let mut stream = TcpStream::connect(address)?;
+    let key = base64(&masking_key().repeat(4));
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        path, address, key
+    )?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut status = String::new();
+    reader.read_line(&mut status)?;
+    if status.split_whitespace().nth(1) != Some("101") {
+        return Err(invalid_data(&format!(
+            "The server refused the upgrade: {}",
+            status.trim()
+        )));
+    }
+
+    let expected = format!("sec-websocket-accept: {}", accept_key(&key));
+    let mut accepted = false;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(invalid_data("The handshake was cut short"));
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        accepted |= line.to_lowercase() == expected.to_lowercase();
+    }
+    if !accepted {
+        return Err(invalid_data("The server sent the wrong accept key"));
+    }
+
+    Ok(WebSocket {
+        reader,
+        sender: Sender {
+            stream,
+            role: Role::Client,
+        },
+    })
+}
+
+fn accept_key(key: &str) -> String {
+    base64(&sha1(format!("{}{}", key.trim(), GUID).as_bytes()))
+}
+
+fn masking_key() -> [u8; 4] {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    let mut x = nanos ^ 0x9e37_79b9;
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    x.to_be_bytes()
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn sha1(message: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [
+        0x6745_2301,
+        0xEFCD_AB89,
+        0x98BA_DCFE,
+        0x1032_5476,
+        0xC3D2_E1F0,
+    ];
+
+    let mut data = message.to_vec();
+    data.push(0x80);
+    while data.len() % 64 != 56 {
+        data.push(0);
+    }
+    data.extend_from_slice(&((message.len() as u64) * 8).to_be_bytes());
+
+    for block in data.chunks(64) {
+        let mut w = [0u32; 80];
+        for i in 0..16 {
+            w[i] = u32::from_be_bytes([
+                block[4 * i],
+                block[4 * i + 1],
+                block[4 * i + 2],
+                block[4 * i + 3],
+            ]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let (mut a, mut b, mut c, mut d, mut e) = (h[0], h[1], h[2], h[3], h[4]);
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ED9_EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+                _ => (b ^ c ^ d, 0xCA62_C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        h[0] = h[0].wrapping_add(a);
+        h[1] = h[1].wrapping_add(b);
+        h[2] = h[2].wrapping_add(c);
+        h[3] = h[3].wrapping_add(d);
+        h[4] = h[4].wrapping_add(e);
+    }
+
+    let mut digest = [0; 20];
+    for (i, value) in h.iter().enumerate() {
+        digest[4 * i..4 * i + 4].copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_key_matches_rfc_example() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn base64_pads_output() {
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+    }
+
+    #[test]
+    fn sha1_of_empty_input() {
+        let digest = sha1(b"");
+        assert_eq!(digest[..4], [0xda, 0x39, 0xa3, 0xee]);
+    }
+}
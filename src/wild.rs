@@ -0,0 +1,60 @@
+use board::Board;
+use game::RuleSet;
+use players::Coordinate;
+use rules::{negamax, negamax_move, Position};
+
+// In wild tic-tac-toe either player may place X or O, and whoever completes
+// a line wins no matter whose mark it's in. Both players have the same moves,
+// so a position is as good for whoever is to move regardless of who they are,
+// and the whole game tree is small enough to search.
+
+pub const MARKS: [char; 2] = ['X', 'O'];
+
+// The best square and mark, preferring quick wins and slow losses.
+pub fn wild_move(board: Board) -> Result<(Coordinate, char), String> {
+    negamax_move(&position(board))
+        .map(|chosen| (chosen.coordinate, chosen.mark))
+        .ok_or_else(|| "No choice found".to_string())
+}
+
+// How good the position is for the player to move with perfect play:
+// positive when they win, negative when they lose and zero for a draw.
+pub fn wild_value(board: Board) -> i32 {
+    negamax(&position(board))
+}
+
+// Who is to move makes no difference, so X always is.
+fn position(board: Board) -> Position {
+    Position::new(board, 'X').with_rules(RuleSet::Wild)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::winning_moves;
+    use win_condition::winning_line;
+
+    #[test]
+    fn first_player_wins() {
+        assert!(wild_value(Board::new()) > 0);
+        let (coordinate, _) = wild_move(Board::new()).unwrap();
+        let next = Board::new().add_value(coordinate, 'X').unwrap();
+        assert!(wild_value(next) < 0);
+    }
+
+    #[test]
+    fn completes_a_line_in_either_mark() {
+        let grid = [['O', 'O', '-'], ['X', '-', '-'], ['X', '-', '-']];
+        let board = Board { grid };
+        let chosen = wild_move(board).unwrap();
+        let next = board.add_value(chosen.0, chosen.1).unwrap();
+        assert!(winning_line(next).is_some());
+
+        let position = Position::new(board, 'X').with_rules(RuleSet::Wild);
+        let squares: Vec<_> = winning_moves(&position)
+            .iter()
+            .map(|m| (m.coordinate, m.mark))
+            .collect();
+        assert_eq!(squares, vec![((2, 0), 'O')]);
+    }
+}
@@ -1,38 +1,90 @@
-extern crate itertools;
-
-use board::Board;
-use win_condition::itertools::Itertools;
+use board::{Board, Grid};
+use players::Coordinate;
+use std::sync::OnceLock;
 
 pub fn is_winning_board(board: Board) -> bool {
-    is_column_win(board) || is_row_win(board) || is_diagonal_win(board)
+    winning_line(board).is_some()
 }
 
-fn is_diagonal_win(board: Board) -> bool {
-    let mut right_diagonal: Vec<char> = Vec::new();
-    let mut left_diagonal: Vec<char> = Vec::new();
-    for x in 0..3 {
-        right_diagonal.push(board.grid[x][x]);
-        left_diagonal.push(board.grid[x][2 - x]);
+// Every line of `length` cells through a grid `size` cells wide along each
+// of its `dimensions` axes, each cell given as one position per axis. Along
+// each axis a line either stays put, counts up or counts down from where it
+// starts; walking a line backwards gives the same line, so only the direction
+// where the last moving axis counts up is kept. That gives the 8 lines of the
+// 3x3 board, the 76 of the 4x4x4 cube and the 32 runs of five on a 6x6 board.
+pub fn enumerate_lines(size: usize, length: usize, dimensions: usize) -> Vec<Vec<Vec<usize>>> {
+    // An axis stays at 0..size, or counts up or down from one of `starts`.
+    let starts = size + 1 - length;
+    let choices = size + 2 * starts;
+    let mut lines = Vec::new();
+
+    for index in 0..choices.pow(dimensions as u32) {
+        let axes: Vec<usize> = (0..dimensions)
+            .map(|axis| index / choices.pow(axis as u32) % choices)
+            .collect();
+        match axes.iter().rev().find(|&&choice| choice >= size) {
+            Some(&choice) if choice < size + starts => {}
+            _ => continue,
+        }
+
+        let line = (0..length)
+            .map(|step| {
+                axes.iter()
+                    .map(|&choice| match choice {
+                        c if c < size => c,
+                        c if c < size + starts => c - size + step,
+                        c => c - size - starts + length - 1 - step,
+                    })
+                    .collect()
+            })
+            .collect();
+        lines.push(line);
     }
+    lines
+}
 
-    unique_non_empty_row(&right_diagonal) || unique_non_empty_row(&left_diagonal)
+// The runs of `length` cells on a grid, like the rows of five on the 6x6
+// board of Order and Chaos or the rows of four on a 7x6 gravity board.
+pub fn grid_lines(width: usize, height: usize, length: usize) -> Vec<Vec<Coordinate>> {
+    enumerate_lines(width.max(height), length, 2)
+        .iter()
+        .filter(|line| line.iter().all(|cell| cell[0] < width && cell[1] < height))
+        .map(|line| line.iter().map(|cell| (cell[0], cell[1])).collect())
+        .collect()
 }
 
-fn unique_non_empty_row(row: &[char]) -> bool {
-    row.into_iter().unique().count() == 1 && row[0] != '-'
+// The first of `lines` filled with a single mark, if any.
+pub fn completed_line<'a>(grid: &Grid, lines: &'a [Vec<Coordinate>]) -> Option<&'a [Coordinate]> {
+    lines
+        .iter()
+        .find(|line| {
+            let first = grid.get(line[0]);
+            first != '-' && line.iter().all(|&cell| grid.get(cell) == first)
+        })
+        .map(|line| line.as_slice())
 }
 
-fn is_column_win(board: Board) -> bool {
-    let transposed = board.transpose();
-    is_row_win(transposed)
+// All eight rows, columns and diagonals.
+pub fn lines() -> &'static [[Coordinate; 3]] {
+    static LINES: OnceLock<Vec<[Coordinate; 3]>> = OnceLock::new();
+    LINES.get_or_init(|| {
+        enumerate_lines(3, 3, 2)
+            .iter()
+            .map(|line| [0, 1, 2].map(|step| (line[step][0], line[step][1])))
+            .collect()
+    })
 }
 
-fn is_row_win(board: Board) -> bool {
-    board
-        .grid
+// The first completed line, if any. Doesn't allocate, which matters to the
+// searching robots.
+pub fn winning_line(board: Board) -> Option<[Coordinate; 3]> {
+    lines()
         .iter()
-        .filter(|x| unique_non_empty_row(*x))
-        .count() > 0
+        .find(|line| {
+            let [a, b, c] = line.map(|(x, y)| board.grid[y][x]);
+            a != '-' && a == b && b == c
+        })
+        .cloned()
 }
 
 #[cfg(test)]
@@ -77,6 +129,64 @@ mod win_condition_tests {
         assert!(!is_winning_board(Board { grid }));
     }
 
+    #[test]
+    fn winning_line_finds_anti_diagonal() {
+        let grid = [['-', '-', 'X'], ['-', 'X', '-'], ['X', '-', 'O']];
+        assert_eq!(winning_line(Board { grid }), Some([(2, 0), (1, 1), (0, 2)]));
+    }
+
+    #[test]
+    fn winning_line_is_none_without_win() {
+        let grid = [['O', '-', '-'], ['-', 'X', '-'], ['-', '-', 'O']];
+        assert_eq!(winning_line(Board { grid }), None);
+    }
+
+    #[test]
+    fn enumerates_every_line_once() {
+        assert_eq!(enumerate_lines(3, 3, 2).len(), 8);
+        assert_eq!(enumerate_lines(4, 4, 3).len(), 76);
+        assert_eq!(enumerate_lines(6, 5, 2).len(), 32);
+        assert_eq!(
+            enumerate_lines(5, 5, 1),
+            vec![vec![vec![0], vec![1], vec![2], vec![3], vec![4]]]
+        );
+    }
+
+    #[test]
+    fn lines_cover_rows_columns_and_diagonals() {
+        let lines = lines();
+        assert_eq!(lines.len(), 8);
+        assert!(lines.contains(&[(0, 1), (1, 1), (2, 1)]));
+        assert!(lines.contains(&[(2, 0), (2, 1), (2, 2)]));
+        assert!(lines.contains(&[(0, 0), (1, 1), (2, 2)]));
+        assert!(lines.contains(&[(2, 0), (1, 1), (0, 2)]));
+    }
+
+    #[test]
+    fn runs_shorter_than_the_grid_start_anywhere() {
+        let lines = grid_lines(6, 6, 5);
+        assert!(lines.contains(&vec![(1, 3), (2, 3), (3, 3), (4, 3), (5, 3)]));
+        assert!(lines.contains(&vec![(5, 0), (4, 1), (3, 2), (2, 3), (1, 4)]));
+        assert!(!lines
+            .iter()
+            .any(|line| line.contains(&(0, 5)) && line.contains(&(5, 0))));
+
+        let mut grid = Grid::new(6);
+        for x in 1..6 {
+            grid = grid.add_value((x, 2), 'O').unwrap();
+        }
+        let expected = [(1, 2), (2, 2), (3, 2), (4, 2), (5, 2)];
+        assert_eq!(completed_line(&grid, &lines), Some(&expected[..]));
+    }
+
+    #[test]
+    fn rectangular_grids_keep_lines_inside() {
+        let lines = grid_lines(7, 6, 4);
+        assert_eq!(lines.len(), 69);
+        assert!(lines.contains(&vec![(3, 0), (4, 1), (5, 2), (6, 3)]));
+        assert!(lines.iter().flatten().all(|&(x, y)| x < 7 && y < 6));
+    }
+
     #[test]
     fn combined_diagonal_is_no_win() {
         let grid = [['O', '-', '-'], ['-', 'X', '-'], ['-', '-', 'O']];